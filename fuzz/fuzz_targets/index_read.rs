@@ -0,0 +1,7 @@
+//! Index::read must never panic
+
+#![no_main]
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    let _ = kff::section::Index::read(&mut std::io::Cursor::new(data));
+});
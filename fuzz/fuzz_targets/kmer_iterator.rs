@@ -0,0 +1,14 @@
+//! Read all kmers of a file with KmerIterator, must never panic
+
+#![no_main]
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    if let Ok(file) = kff::Kff::read(std::io::Cursor::new(data)) {
+        for kmer in file.kmers() {
+            // Reader state is undefined after an error
+            if kmer.is_err() {
+                break;
+            }
+        }
+    }
+});
@@ -0,0 +1,9 @@
+//! Values::read must never panic
+
+#![no_main]
+
+use kff::section::values::AbcValues as _;
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    let _ = kff::section::Values::read(&mut std::io::Cursor::new(data));
+});
@@ -58,6 +58,10 @@ pub enum Kff {
     #[error("Section at position isn't an index section")]
     NotAnIndex,
 
+    /// Chain of index sections come back to an index already read
+    #[error("Index at position {0} is already read, chain of index loops")]
+    IndexLoop(u64),
+
     /// No 'first_index' in footer
     #[error("Variable 'first_index' not present in footer it's seems not be an indexed Kff file")]
     NoFirstIndex,
@@ -73,6 +77,142 @@ pub enum Kff {
     /// Not a kmer section
     #[error("Not a kmer section, you try to read a section isn't ")]
     NotAKmerSection,
+
+    /// Headers of files can't be merged
+    #[error("Header isn't compatible, encoding and canonical flag must be the same")]
+    HeaderNotCompatible,
+
+    /// Data present after final signature
+    #[error("Data present after final signature")]
+    DataAfterSignature,
+
+    /// Line of a text file can't be parsed
+    #[error("Line {0} isn't valid: {1}")]
+    BadTextLine(u64, String),
+
+    /// Sequence file isn't a valid fasta or fastq
+    #[error("Record {0} isn't a valid fasta or fastq record: {1}")]
+    BadSequenceRecord(u64, String),
+
+    /// Minimizer size is larger than kmer size
+    #[error("Minimizer size {0} must be lower or equal to kmer size {1}")]
+    MinimizerSizeTooLarge(u64, u64),
+
+    /// Kmer size is 0
+    #[error("Kmer size must be greater than 0")]
+    KmerSizeIsZero,
+
+    /// KMC database can't be read
+    #[error("KMC database isn't valid or supported: {0}")]
+    BadKmcDatabase(String),
+
+    /// Kmer index sidecar doesn't begin with magic
+    #[error("Not a kmer index, magic number KFFI is missing")]
+    NotAKmerIndex,
+
+    /// Kmer bloom filter doesn't begin with magic
+    #[error("Not a kmer bloom filter, magic number KFFB is missing")]
+    NotAKmerBloom,
+
+    /// Parameters of kmer bloom filter aren't valid
+    #[error("Kmer bloom filter isn't valid: {0}")]
+    BadKmerBloom(String),
+
+    /// False positive rate isn't valid
+    #[error("False positive rate {0} isn't between 0 and 1 excluded")]
+    FalsePositiveRate(f64),
+
+    /// Kmer map doesn't begin with magic
+    #[error("Not a kmer map, magic number KFFM is missing")]
+    NotAKmerMap,
+
+    /// Parameters of kmer map aren't valid
+    #[error("Kmer map isn't valid: {0}")]
+    BadKmerMap(String),
+
+    /// Gamma of minimal perfect hash function isn't valid
+    #[error("Gamma {0} must be greater or equal to 1")]
+    BadGamma(f64),
+
+    /// Kmers haven't same data size
+    #[error("Data size {1} of kmer isn't data size {0} of previous kmers")]
+    DataSizeMismatch(usize, usize),
+
+    /// Kmers haven't same size
+    #[error("Kmer size {1} isn't kmer size {0} of section")]
+    KmerSizeMismatch(u64, u64),
+
+    /// Section variable isn't equal to value of last Values section
+    #[error("Variable '{0}' of section is {2} but last Values section set it to {1}")]
+    SectionValuesMismatch(String, u64, u64),
+
+    /// Block contains more kmer than max
+    #[error("Block contains {0} kmers but max is {1}")]
+    BlockTooLong(u64, u64),
+
+    /// Block sequence is shorter than k
+    #[error("Block sequence of {0} nucleotides is shorter than kmer size {1}")]
+    BlockTooShort(u64, u64),
+
+    /// Length of block data isn't number of kmer times data_size
+    #[error("Block data length is {1} bytes but {0} bytes are expected")]
+    BadDataLength(u64, u64),
+
+    /// Minimizer size isn't m
+    #[error("Minimizer size is {1} but m is {0}")]
+    MinimizerSizeMismatch(u64, u64),
+
+    /// Section minimizer isn't at minimizer offset of block
+    #[error("Minimizer isn't present at offset {0} of block")]
+    MinimizerNotFound(u64),
+
+    /// Minimizer isn't shared by all kmers of block
+    #[error("Minimizer isn't present in all kmers of block")]
+    MinimizerNotInBlock,
+
+    /// Max is 0, a block must contain at least one kmer
+    #[error("Value max must be greater than 0")]
+    MaxIsZero,
+
+    /// Number of kmer of a block isn't between 1 and max
+    #[error("Block contains {0} kmers, it must be between 1 and max {1}")]
+    InvalidNbKmer(u64, u64),
+
+    /// Minimizer offset is after end of block
+    #[error("Minimizer offset {0} is larger than {1}, length of block without minimizer")]
+    MinimizerOffsetTooLarge(u64, u64),
+
+    /// A size read is larger than limit
+    #[error("Read {0} {1} is larger than limit {2}")]
+    LimitExceeded(String, u64, u64),
+
+    /// Header free block isn't key-value metadata
+    #[error("Header free block isn't key-value metadata")]
+    NotMetadata,
+
+    /// Metadata key or value contains a forbidden character
+    #[error("Metadata key or value {0:?} is invalid")]
+    InvalidMetadata(String),
+
+    /// Header can't be rewritten in place because its size change
+    #[error("Header size change from {0} to {1} bytes, it can't be rewritten in place")]
+    HeaderSizeMismatch(u64, u64),
+
+    /// Checksum in footer doesn't match checksum of file
+    #[error("Checksum in footer {0:#x} doesn't match checksum of file {1:#x}")]
+    ChecksumMismatch(u64, u64),
+
+    /// Sections was read before validation, checksum can't be computed
+    #[error("Sections was already read, file can't be validated")]
+    SectionsAlreadyRead,
+
+    /// A section is write after footer
+    #[error("Footer is already written, only final signature can follow it")]
+    WriteAfterFooter,
+
+    /// Value can't be store in number of bytes choose by max
+    #[error("Value {0} can't be store in field sized for {1}")]
+    ValueOverflow(u64, u64),
 }
 
 /// Alias of result
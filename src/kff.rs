@@ -1,9 +1,9 @@
 //! Kmer File Format Rust parser
 
 /* std use */
+use std::io::Seek as _;
 
 /* crate use */
-use std::io::Seek;
 
 /* project use */
 use crate::error;
@@ -11,16 +11,21 @@ use crate::section;
 use crate::utils;
 use crate::GlobalIndex;
 use crate::KffRead;
+use crate::KffWrite;
 use crate::Kmer;
 use crate::KmerIterator;
 
 use crate::section::values::AbcValues as _;
 
+/// Name of last footer variable
+const FOOTER_SIZE: &[u8] = b"footer_size\0";
+
 /// Struct to read a kff file
 #[derive(getset::Getters, getset::Setters, getset::MutGetters)]
 #[getset(get = "pub")]
 pub struct Kff<T> {
     /// Inner read source
+    #[getset(get_mut = "pub(crate)")]
     inner: T,
 
     /// Header extract from `inner`
@@ -33,6 +38,50 @@ pub struct Kff<T> {
 
     /// GlobalIndex present only if inner is seekable and first section is index or footer contains first_index
     index: Option<utils::GlobalIndex>,
+
+    /// Number of bytes write in `inner`, only used in write mode
+    #[getset(skip)]
+    position: u64,
+
+    /// Type and position of sections write in `inner`, only used in write mode
+    #[getset(skip)]
+    sections: Vec<(u8, u64)>,
+
+    /// Minimizer to position in `index` of Minimizer sections, build on first use
+    #[getset(skip)]
+    minimizers: Option<rustc_hash::FxHashMap<crate::Seq2Bit, Vec<usize>>>,
+
+    /// Limits check when sections are read
+    #[getset(set = "pub")]
+    limits: crate::ReadLimits,
+
+    /// Checksum of bytes write in `inner` until footer, only used in write mode if enabled
+    #[getset(skip)]
+    checksum: Option<utils::Crc32c>,
+
+    /// Checksum of header bytes as read, only used in read mode
+    #[getset(skip)]
+    header_checksum: utils::Crc32c,
+
+    /// Position of last index of file open in append mode, new index is chained to it
+    #[getset(skip)]
+    previous_index: Option<u64>,
+
+    /// Call on `inner` after final signature is written, only used in append mode
+    #[getset(skip)]
+    on_finalize: Option<fn(&mut T) -> std::io::Result<()>>,
+
+    /// Variables of footer of file open in append mode, they are written again in new footer
+    #[getset(skip)]
+    inherited_footer: Option<section::Values>,
+
+    /// A footer was written, only used in write mode
+    #[getset(skip)]
+    footer_written: bool,
+
+    /// Bytes after header was read, only used in read mode
+    #[getset(skip)]
+    sections_read: bool,
 }
 
 impl<R> Kff<R>
@@ -40,8 +89,15 @@ where
     R: std::io::Read + std::io::BufRead + crate::KffRead,
 {
     /// Create a new Kff reader by accept mutable reference on [std::io::Read]
-    pub fn read(mut inner: R) -> error::Result<Self> {
-        let header = section::Header::read(&mut inner)?;
+    pub fn read(inner: R) -> error::Result<Self> {
+        Self::read_with_limits(inner, crate::ReadLimits::default())
+    }
+
+    /// Create a new Kff reader, header and sections are read with `limits`
+    pub fn read_with_limits(mut inner: R, limits: crate::ReadLimits) -> error::Result<Self> {
+        let mut hasher = utils::checksum::Hasher::new(&mut inner, utils::Crc32c::new());
+        let header = section::Header::read_with_limits(&mut hasher, &limits)?;
+        let header_checksum = hasher.checksum();
         let values = section::Values::default();
 
         Ok(Self {
@@ -49,6 +105,17 @@ where
             header,
             values,
             index: None,
+            position: 0,
+            sections: Vec::new(),
+            minimizers: None,
+            limits,
+            checksum: None,
+            header_checksum,
+            previous_index: None,
+            on_finalize: None,
+            inherited_footer: None,
+            footer_written: false,
+            sections_read: false,
         })
     }
 
@@ -59,33 +126,116 @@ where
 
     /// Read Kff until next kmer section
     pub fn next_kmer_section(&mut self) -> std::option::Option<error::Result<Vec<Kmer>>> {
+        self.next_block_section()
+            .map(|section| section.map(|x| x.into_blocks().into_iter().flatten().collect()))
+    }
+
+    /// Read Kff until next kmer section and keep kmer grouped by block
+    pub fn next_block_section(
+        &mut self,
+    ) -> std::option::Option<error::Result<section::KmerSection>> {
+        self.sections_read = true;
         loop {
-            match self.inner.read_u8() {
-                Ok(b'v') => {
-                    self.values = {
-                        match section::Values::read(&mut self.inner) {
-                            Err(e) => return Some(Err(e)),
-                            Ok(v) => v,
-                        }
-                    }
+            match next_prefix(&mut self.inner, &mut self.values, &self.limits) {
+                Ok(Prefix::Raw) => {
+                    return Some(
+                        raw_section(&self.values, self.limits)
+                            .and_then(|section| section.read_blocks(&mut self.inner))
+                            .map(section::KmerSection::Raw),
+                    )
                 }
-                Ok(b'r') => match section::Raw::new(&self.values) {
-                    Ok(section) => return Some(section.read(&mut self.inner)),
-                    Err(e) => return Some(Err(e)),
-                },
-                Ok(b'm') => match section::Minimizer::new(&self.values) {
-                    Ok(section) => return Some(section.read(&mut self.inner)),
-                    Err(e) => return Some(Err(e)),
-                },
-                Ok(b'K') => return None, // It's the begin of last signature stop reading
-                Ok(b'i') => match section::Index::skip(&mut self.inner) {
-                    Err(e) => return Some(Err(e)),
-                    Ok(_) => continue,
-                },
-                Ok(e) => return Some(Err(error::Kff::NotASectionPrefix(e).into())), // Any other value is an error
+                Ok(Prefix::Minimizer) => {
+                    return Some(
+                        minimizer_section(&self.values, self.limits)
+                            .and_then(|section| section.read_blocks(&mut self.inner))
+                            .map(|(m, b)| section::KmerSection::Minimizer(m, b)),
+                    )
+                }
+                Ok(Prefix::End) => return None, // It's the begin of last signature stop reading
+                Ok(Prefix::Values | Prefix::Index) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Read Kff until next kmer section and get section type and bytes of section without decoding it
+    ///
+    /// Section flag isn't include in bytes.
+    pub fn next_section_bytes(&mut self) -> std::option::Option<error::Result<(u8, Vec<u8>)>> {
+        self.sections_read = true;
+        loop {
+            let section_type = match next_prefix(&mut self.inner, &mut self.values, &self.limits) {
+                Ok(Prefix::Raw) => b'r',
+                Ok(Prefix::Minimizer) => b'm',
+                Ok(Prefix::End) => return None, // It's the begin of last signature stop reading
+                Ok(Prefix::Values | Prefix::Index) => continue,
                 Err(e) => return Some(Err(e)),
+            };
+
+            // Skip check limits as blocks are recorded
+            let mut recorder = utils::read::Recorder::new(&mut self.inner);
+            let skip = if section_type == b'r' {
+                raw_section(&self.values, self.limits).and_then(|s| s.skip(&mut recorder))
+            } else {
+                minimizer_section(&self.values, self.limits).and_then(|s| s.skip(&mut recorder))
+            };
+
+            return Some(skip.map(|_| (section_type, recorder.into_record())));
+        }
+    }
+
+    /// Read and decode all sections, check final signature and that nothing follows it
+    ///
+    /// If footer contains a `crc32c` variable, it must be equal to a single CRC32C of header, as
+    /// read when Kff was created, and all sections before footer, sections haven't their own
+    /// checksum. Checksum is computed on bytes read by this function, so if any section was
+    /// already read, or Kff was seek, [error::Kff::SectionsAlreadyRead] is returned.
+    pub fn validate(&mut self) -> error::Result<()> {
+        if self.sections_read {
+            return Err(error::Kff::SectionsAlreadyRead.into());
+        }
+        self.sections_read = true;
+
+        let mut inner = utils::checksum::Hasher::new(&mut self.inner, self.header_checksum);
+        // Last section and checksum of bytes before it
+        let mut last = (Prefix::End, self.header_checksum);
+        loop {
+            let before = inner.checksum();
+            let prefix = next_prefix(&mut inner, &mut self.values, &self.limits)?;
+            match prefix {
+                Prefix::Raw => {
+                    raw_section(&self.values, self.limits)?.read_blocks(&mut inner)?;
+                }
+                Prefix::Minimizer => {
+                    minimizer_section(&self.values, self.limits)?.read_blocks(&mut inner)?;
+                }
+                Prefix::End => break, // It's the begin of last signature stop reading
+                Prefix::Values | Prefix::Index => (),
             }
+            last = (prefix, before);
         }
+
+        // First byte of signature is already consume
+        if &self.inner.read_n_bytes::<2>()? != b"FF" {
+            return Err(error::Kff::MissingMagic("end".to_string()).into());
+        }
+
+        if !self.inner.fill_buf()?.is_empty() {
+            return Err(error::Kff::DataAfterSignature.into());
+        }
+
+        // Last section is footer
+        if let (Prefix::Values, before) = last {
+            if let Some(expected) = self.values.get(utils::checksum::FOOTER_NAME) {
+                if *expected != before.value() as u64 {
+                    return Err(
+                        error::Kff::ChecksumMismatch(*expected, before.value() as u64).into(),
+                    );
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -105,30 +255,66 @@ impl Kff<std::io::BufReader<std::fs::File>> {
     where
         P: std::convert::AsRef<std::path::Path>,
     {
-        let mut inner = std::fs::File::open(&path).map(std::io::BufReader::new)?;
+        std::fs::File::open(&path)
+            .map(std::io::BufReader::new)
+            .map(Kff::read_with_index)?
+    }
+}
 
-        let header = section::Header::read(&mut inner)?;
+impl<R> Kff<R>
+where
+    R: std::io::Read + std::io::BufRead + std::io::Seek + crate::KffRead,
+{
+    /// Create a new Kff reader and generate a global index
+    ///
+    /// At the end of construction position in `inner` is the start of the first section
+    pub fn read_with_index(inner: R) -> error::Result<Self> {
+        Self::read_with_index_and_limits(inner, crate::ReadLimits::default())
+    }
+
+    /// Same as [Kff::read_with_index] but header, footer, index and sections are read with `limits`
+    pub fn read_with_index_and_limits(
+        mut inner: R,
+        limits: crate::ReadLimits,
+    ) -> error::Result<Self> {
+        let mut hasher = utils::checksum::Hasher::new(&mut inner, utils::Crc32c::new());
+        let header = section::Header::read_with_limits(&mut hasher, &limits)?;
+        let header_checksum = hasher.checksum();
         let values = section::Values::default();
 
         let pos_first_section = inner.stream_position()?;
-        let index = match utils::GlobalIndex::new(&mut inner, pos_first_section) {
+        let index = match GlobalIndex::new_with_limits(&mut inner, pos_first_section, &limits) {
             Ok(index) => Some(index),
             Err(error::Error::Kff(error::Kff::NotAnIndex)) => {
-                let value = Kff::load_footer(&mut inner)?;
+                let value = Kff::load_footer(&mut inner, &limits)?;
 
-                Some(GlobalIndex::new(
+                Some(GlobalIndex::new_with_limits(
                     &mut inner,
                     *value.get("first_index").ok_or(error::Kff::NoFirstIndex)?,
+                    &limits,
                 )?)
             }
             Err(e) => return Err(e),
         };
 
+        inner.seek(std::io::SeekFrom::Start(pos_first_section))?;
+
         Ok(Self {
             inner,
             header,
             values,
             index,
+            position: 0,
+            sections: Vec::new(),
+            minimizers: None,
+            limits,
+            checksum: None,
+            header_checksum,
+            previous_index: None,
+            on_finalize: None,
+            inherited_footer: None,
+            footer_written: false,
+            sections_read: false,
         })
     }
 }
@@ -159,7 +345,7 @@ where
     }
 
     /// Load footer, assume last section is a value and last value of this section is footer_size
-    fn load_footer(inner: &mut R) -> error::Result<section::Values> {
+    fn load_footer(inner: &mut R, limits: &crate::ReadLimits) -> error::Result<section::Values> {
         inner.seek(std::io::SeekFrom::End(-11))?;
         let footer_size = inner.read_u64()?;
 
@@ -169,7 +355,7 @@ where
         if v != b'v' {
             Err(error::Kff::FooterSizeNotCorrect.into())
         } else {
-            section::Values::read(inner)
+            section::Values::read_with_limits(inner, limits)
         }
     }
 
@@ -179,32 +365,145 @@ where
     /// If we didn't found section value before target section return an Error
     /// If section isn't a kmer section return an Error
     pub fn kmer_of_section(&mut self, n: usize) -> error::Result<Vec<Kmer>> {
+        self.block_section_of(n)
+            .map(|section| section.into_blocks().into_iter().flatten().collect())
+    }
+
+    /// Get blocks, and minimizer, of nth section in index.
+    ///
+    /// Errors are the same as [Kff::kmer_of_section]
+    pub fn block_section_of(&mut self, n: usize) -> error::Result<section::KmerSection> {
+        self.values = self.values_of(n)?;
+
+        let position = self
+            .index
+            .as_ref()
+            .ok_or(error::Error::Kff(error::Kff::NoIndex))?
+            .pair()[n]
+            .1;
+        self.inner.seek(std::io::SeekFrom::Start(position))?;
+        match self.inner.read_u8()? {
+            b'r' => raw_section(&self.values, self.limits)?
+                .read_blocks(&mut self.inner)
+                .map(section::KmerSection::Raw),
+            b'm' => minimizer_section(&self.values, self.limits)?
+                .read_blocks(&mut self.inner)
+                .map(|(m, b)| section::KmerSection::Minimizer(m, b)),
+            _ => Err(error::Kff::NotAKmerSection.into()),
+        }
+    }
+
+    /// Read last Values section before nth section in index, without read nth section
+    pub(crate) fn values_of(&mut self, n: usize) -> error::Result<section::Values> {
         let index = self
             .index
             .as_ref()
             .ok_or(error::Error::Kff(error::Kff::NoIndex))?;
 
-        self.values = match index.pair()[..n].iter().rev().find(|x| x.0 == b'v') {
+        self.sections_read = true;
+        match index.pair()[..n].iter().rev().find(|x| x.0 == b'v') {
             Some((_t, p)) => {
                 self.inner.seek(std::io::SeekFrom::Start(p + 1))?;
-                section::Values::read(&mut self.inner)?
+                section::Values::read_with_limits(&mut self.inner, &self.limits)
             }
-            None => return Err(error::Kff::NoValueSectionBeforeTarget.into()),
-        };
+            None => Err(error::Kff::NoValueSectionBeforeTarget.into()),
+        }
+    }
 
-        self.inner
-            .seek(std::io::SeekFrom::Start(index.pair()[n].1))?;
-        match self.inner.read_u8()? {
-            b'r' => match section::Raw::new(&self.values) {
-                Ok(section) => section.read(&mut self.inner),
-                Err(e) => Err(e),
-            },
-            b'm' => match section::Minimizer::new(&self.values) {
-                Ok(section) => section.read(&mut self.inner),
-                Err(e) => Err(e),
-            },
-            _ => Err(error::Kff::NotAKmerSection.into()),
+    /// Get map from minimizer to position in index of Minimizer sections with this minimizer
+    ///
+    /// Map is build on first call, only values sections and minimizer of each Minimizer section
+    /// are read.
+    ///
+    /// If index isn't set return an Error
+    pub fn minimizers(
+        &mut self,
+    ) -> error::Result<&rustc_hash::FxHashMap<crate::Seq2Bit, Vec<usize>>> {
+        if self.minimizers.is_none() {
+            let index = self
+                .index
+                .as_ref()
+                .ok_or(error::Error::Kff(error::Kff::NoIndex))?;
+
+            self.sections_read = true;
+            self.minimizers = Some(index.minimizers(&mut self.inner, &self.limits)?);
+        }
+
+        Ok(self.minimizers.get_or_insert_with(Default::default))
+    }
+
+    /// Get kmers of all Minimizer sections with this minimizer, in file order
+    ///
+    /// To check if a kmer is present compute its minimizer, with
+    /// [minimizer](crate::kmer::minimizer) for file write by this crate, and search kmer in
+    /// result. See [Kff::minimizers].
+    ///
+    /// If index isn't set return an Error
+    pub fn sections_with_minimizer(
+        &mut self,
+        minimizer: &crate::Seq2Bit,
+    ) -> error::Result<Vec<Kmer>> {
+        let sections = self
+            .minimizers()?
+            .get(minimizer)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut kmers = Vec::new();
+        for n in sections {
+            kmers.extend(self.kmer_of_section(n)?);
+        }
+
+        Ok(kmers)
+    }
+
+    /// Get blocks, and minimizer, of kmer section begin at `position`, section must be in index.
+    ///
+    /// Errors are the same as [Kff::kmer_of_section]
+    pub fn block_section_at(&mut self, position: u64) -> error::Result<section::KmerSection> {
+        let n = self
+            .index
+            .as_ref()
+            .ok_or(error::Error::Kff(error::Kff::NoIndex))?
+            .pair()
+            .iter()
+            .position(|(_, p)| *p == position)
+            .ok_or(error::Kff::NotAKmerSection)?;
+
+        self.block_section_of(n)
+    }
+
+    /// Decode all kmer sections in index to build a [KmerIndex](utils::KmerIndex)
+    ///
+    /// If index isn't set return an Error
+    pub fn kmer_index(&mut self) -> error::Result<utils::KmerIndex> {
+        let pairs = self
+            .index
+            .as_ref()
+            .ok_or(error::Error::Kff(error::Kff::NoIndex))?
+            .pair()
+            .clone();
+
+        let mut sections = Vec::new();
+        for (n, (section_type, position)) in pairs.iter().enumerate() {
+            if !matches!(section_type, b'r' | b'm') {
+                continue;
+            }
+
+            let section = self.block_section_of(n)?;
+            if let Some(bounds) = utils::kmer_index::SectionBounds::new(*position, section) {
+                sections.push(bounds);
+            }
         }
+
+        Ok(utils::KmerIndex::from_sections(sections))
+    }
+}
+
+impl<T> Kff<T> {
+    /// Consume Kff object to get inner
+    pub fn into_inner(self) -> T {
+        self.inner
     }
 }
 
@@ -213,6 +512,7 @@ where
     T: std::io::Read + std::io::Seek,
 {
     fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.sections_read = true;
         self.inner.seek(pos)
     }
 }
@@ -222,62 +522,281 @@ where
     W: std::io::Write + crate::KffWrite,
 {
     /// Create a Kff object to write in inner
-    pub fn write(mut inner: W, header: section::Header) -> error::Result<Self> {
-        header.write(&mut inner)?;
+    pub fn write(inner: W, header: section::Header) -> error::Result<Self> {
+        Self::init_write(inner, header, None)
+    }
+
+    /// Create a Kff object to write in inner, a CRC32C of all bytes before footer is write in footer
+    ///
+    /// It's a single checksum of whole file, sections haven't their own checksum. Footer is always
+    /// written, by [Kff::finalize] if no footer was written before.
+    pub fn write_with_checksum(inner: W, header: section::Header) -> error::Result<Self> {
+        Self::init_write(inner, header, Some(utils::Crc32c::new()))
+    }
+
+    fn init_write(
+        mut inner: W,
+        header: section::Header,
+        mut checksum: Option<utils::Crc32c>,
+    ) -> error::Result<Self> {
+        let mut counter = utils::write::Counter::with_checksum(&mut inner, checksum.as_mut());
+        header.write(&mut counter)?;
+        let position = counter.count();
 
         Ok(Self {
             inner,
             header,
             values: section::Values::default(),
             index: None,
+            position,
+            sections: Vec::new(),
+            minimizers: None,
+            limits: crate::ReadLimits::default(),
+            checksum,
+            header_checksum: utils::Crc32c::new(),
+            previous_index: None,
+            on_finalize: None,
+            inherited_footer: None,
+            footer_written: false,
+            sections_read: false,
         })
     }
 
     /// Write a Values section
     pub fn write_values(&mut self, values: section::Values) -> error::Result<()> {
+        self.check_not_finished()?;
+
         self.values = values;
 
-        self.inner.write_bytes(b"v")?;
-        self.values.write(&mut self.inner)?;
+        let mut counter =
+            utils::write::Counter::with_checksum(&mut self.inner, self.checksum.as_mut());
+        counter.write_bytes(b"v")?;
+        self.values.write(&mut counter)?;
+        let count = counter.count();
+
+        self.add_section(b'v', count);
 
         Ok(())
     }
 
     /// Write a Index section
     pub fn write_index(&mut self, index: section::Index) -> error::Result<()> {
-        self.inner.write_bytes(b"i")?;
-        index.write(&mut self.inner)
+        self.check_not_finished()?;
+
+        let mut counter =
+            utils::write::Counter::with_checksum(&mut self.inner, self.checksum.as_mut());
+        counter.write_bytes(b"i")?;
+        index.write(&mut counter)?;
+
+        self.position += counter.count();
+
+        Ok(())
     }
 
     /// Write a Raw section
+    ///
+    /// `k`, `max` and `data_size` of section must be equal to last Values section and blocks must
+    /// match them, otherwise an error is returned and nothing is write.
     pub fn write_raw(
         &mut self,
         raw: section::Raw,
 
         blocks: &[section::block::Block],
     ) -> error::Result<()> {
-        self.inner.write_bytes(b"r")?;
-        raw.write(&mut self.inner, blocks)
+        self.check_not_finished()?;
+        self.check_values(&[
+            ("k", *raw.k()),
+            ("max", *raw.max()),
+            ("data_size", *raw.data_size()),
+        ])?;
+        raw.validate(blocks)?;
+
+        let mut counter =
+            utils::write::Counter::with_checksum(&mut self.inner, self.checksum.as_mut());
+        counter.write_bytes(b"r")?;
+        raw.write_unchecked(&mut counter, blocks)?;
+        let count = counter.count();
+
+        self.add_section(b'r', count);
+
+        Ok(())
     }
 
     /// Write a Minimizer section
+    ///
+    /// `k`, `m`, `max` and `data_size` of section must be equal to last Values section and
+    /// minimizer and blocks must match them, otherwise an error is returned and nothing is write.
     pub fn write_minimizer(
         &mut self,
         section: section::Minimizer,
         minimizer: crate::Seq2Bit,
         blocks: &[section::block::Block],
     ) -> error::Result<()> {
-        self.inner.write_bytes(b"m")?;
-        section.write(&mut self.inner, minimizer, blocks)
+        self.check_not_finished()?;
+        self.check_values(&[
+            ("k", *section.k()),
+            ("m", *section.m()),
+            ("max", *section.max()),
+            ("data_size", *section.data_size()),
+        ])?;
+        section.validate(&minimizer, blocks)?;
+
+        let mut counter =
+            utils::write::Counter::with_checksum(&mut self.inner, self.checksum.as_mut());
+        counter.write_bytes(b"m")?;
+        section.write_unchecked(&mut counter, minimizer, blocks)?;
+        let count = counter.count();
+
+        self.add_section(b'm', count);
+
+        Ok(())
+    }
+
+    /// Check no footer was written, nothing can follow it except final signature
+    fn check_not_finished(&self) -> error::Result<()> {
+        if self.footer_written {
+            Err(error::Kff::WriteAfterFooter.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check variables of a section are equal to last Values section
+    fn check_values(&self, variables: &[(&str, u64)]) -> error::Result<()> {
+        for (name, value) in variables {
+            let expected = *self
+                .values
+                .get(*name)
+                .ok_or_else(|| error::Kff::FieldIsMissing(name.to_string()))?;
+
+            if expected != *value {
+                return Err(
+                    error::Kff::SectionValuesMismatch(name.to_string(), expected, *value).into(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write a Raw or Minimizer section already encoded, section flag isn't include in bytes
+    pub(crate) fn write_section_bytes(
+        &mut self,
+        section_type: u8,
+        bytes: &[u8],
+    ) -> error::Result<()> {
+        self.check_not_finished()?;
+
+        let mut counter =
+            utils::write::Counter::with_checksum(&mut self.inner, self.checksum.as_mut());
+        counter.write_u8(&section_type)?;
+        counter.write_bytes(bytes)?;
+
+        self.add_section(section_type, 1 + bytes.len() as u64);
+
+        Ok(())
     }
 
-    /// Finalize write the final signature
+    /// Write a footer, a Values section with `footer_size` as last variable
+    ///
+    /// After footer only [Kff::finalize] can be called, other writes return
+    /// [error::Kff::WriteAfterFooter].
+    ///
+    /// If checksum is enabled, `crc32c` is add to footer and checksum is stopped. In append mode,
+    /// variables of previous footer, `first_index` included, are added if `footer` doesn't contain
+    /// them.
+    pub fn write_footer(&mut self, footer: section::Values) -> error::Result<()> {
+        self.check_not_finished()?;
+
+        let mut footer = match self.inherited_footer.take() {
+            Some(mut inherited) => {
+                inherited.extend(footer);
+                inherited
+            }
+            None => footer,
+        };
+
+        if let Some(checksum) = self.checksum.take() {
+            footer.insert(
+                utils::checksum::FOOTER_NAME.to_string(),
+                checksum.value() as u64,
+            );
+        }
+
+        // Checksum is already taken, footer isn't part of it
+        let mut counter = utils::write::Counter::with_checksum(&mut self.inner, None);
+        counter.write_bytes(b"v")?;
+        footer.write_as_footer(&mut counter)?;
+
+        self.position += counter.count();
+        self.footer_written = true;
+
+        Ok(())
+    }
+
+    /// Finalize write the final signature, preceded by a footer if checksum is enabled and no footer was written
+    ///
+    /// In append mode, if file had an index, this call [Kff::finalize_with_index] so the new index
+    /// chain to the previous one, and if previous footer had other variables a footer is written.
     pub fn finalize(&mut self) -> error::Result<()> {
+        if !self.footer_written {
+            if self.previous_index.is_some() {
+                return self.finalize_with_index();
+            }
+
+            if self.checksum.is_some() || self.inherited_footer.is_some() {
+                self.write_footer(section::Values::default())?;
+            }
+        }
+
+        self.write_signature()
+    }
+
+    /// Write the final signature and flush
+    fn write_signature(&mut self) -> error::Result<()> {
         self.inner.write_bytes(b"KFF")?;
         self.inner.flush()?;
 
+        if let Some(on_finalize) = self.on_finalize.take() {
+            on_finalize(&mut self.inner)?;
+        }
+
         Ok(())
     }
+
+    /// Finalize write an index of all Values, Raw and Minimizer sections, a footer with `first_index` and the final signature
+    ///
+    /// In append mode, new index is chained to last index of file through `next_index`.
+    pub fn finalize_with_index(&mut self) -> error::Result<()> {
+        let first_index = self.position;
+        let index_end = first_index + 1 + 8 + self.sections.len() as u64 * 9 + 8;
+
+        // Like in GlobalIndex, next_index point to byte after index flag
+        let next_index = self
+            .previous_index
+            .map(|previous| (previous as i64 + 1 - index_end as i64) as u64)
+            .unwrap_or(0);
+
+        let index = section::Index::new(
+            self.sections
+                .iter()
+                .map(|(t, p)| (*t, *p as i64 - index_end as i64))
+                .collect(),
+            next_index,
+        );
+        self.write_index(index)?;
+
+        let mut footer = section::Values::default();
+        footer.insert("first_index".to_string(), first_index);
+        self.write_footer(footer)?;
+
+        self.write_signature()
+    }
+
+    fn add_section(&mut self, section_type: u8, size: u64) {
+        self.sections.push((section_type, self.position));
+        self.position += size;
+    }
 }
 
 impl Kff<std::io::BufWriter<std::fs::File>> {
@@ -292,6 +811,248 @@ impl Kff<std::io::BufWriter<std::fs::File>> {
     }
 }
 
+impl Kff<utils::AppendFile> {
+    /// Open an existing file to write new sections after its last section
+    ///
+    /// New sections overwrite final signature and footer, which are restored if writer is dropped
+    /// before [Kff::finalize], see [AppendFile](utils::AppendFile). Other variables of footer are
+    /// written again in new footer.
+    ///
+    /// If footer contains `first_index`, the index written by [Kff::finalize_with_index], or by
+    /// [Kff::finalize], only contains new sections and is chained to previous index, otherwise
+    /// existing sections are scanned and indexed with new ones. If footer contains `crc32c`, it
+    /// must match existing bytes and checksum is continued from them. Values of existing sections
+    /// aren't loaded, a Values section must be written before first kmer section.
+    pub fn open_append<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        Self::open_append_with_limits(path, crate::ReadLimits::default())
+    }
+
+    /// Same as [Kff::open_append] but header, footer and existing sections are read with `limits`
+    pub fn open_append_with_limits<P>(path: P, limits: crate::ReadLimits) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        let mut reader = std::io::BufReader::new(&mut file);
+        let header = section::Header::read_with_limits(&mut reader, &limits)?;
+        let first_section = reader.stream_position()?;
+
+        let end = reader.seek(std::io::SeekFrom::End(0))?;
+        if end < first_section + 3 {
+            return Err(error::Kff::MissingMagic("end".to_string()).into());
+        }
+        reader.seek(std::io::SeekFrom::Start(end - 3))?;
+        if &reader.read_n_bytes::<3>()? != b"KFF" {
+            return Err(error::Kff::MissingMagic("end".to_string()).into());
+        }
+
+        let (footer, position) = match find_footer(&mut reader, end - 3, &limits)? {
+            Some((footer, position)) => (footer, position),
+            None => (section::Values::default(), end - 3),
+        };
+
+        let previous_index = footer.get("first_index").cloned();
+        let sections = if previous_index.is_some() {
+            Vec::new()
+        } else {
+            reader.seek(std::io::SeekFrom::Start(first_section))?;
+            Self::scan_sections(&mut reader, position, &limits)?
+        };
+
+        let checksum = match footer.get(utils::checksum::FOOTER_NAME) {
+            Some(expected) => {
+                reader.seek(std::io::SeekFrom::Start(0))?;
+                let checksum = utils::checksum::crc32c_of(&mut reader, position)?;
+                if *expected != checksum.value() as u64 {
+                    return Err(
+                        error::Kff::ChecksumMismatch(*expected, checksum.value() as u64).into(),
+                    );
+                }
+
+                Some(checksum)
+            }
+            None => None,
+        };
+        drop(reader);
+
+        // first_index is replaced if a new index is written
+        let mut inherited_footer = footer;
+        inherited_footer.remove(utils::checksum::FOOTER_NAME);
+
+        Ok(Self {
+            inner: utils::AppendFile::new(file, position)?,
+            header,
+            values: section::Values::default(),
+            index: None,
+            position,
+            sections,
+            minimizers: None,
+            limits,
+            checksum,
+            header_checksum: utils::Crc32c::new(),
+            previous_index,
+            on_finalize: Some(utils::AppendFile::commit),
+            inherited_footer: (!inherited_footer.is_empty()).then_some(inherited_footer),
+            footer_written: false,
+            sections_read: false,
+        })
+    }
+
+    /// Get type and position of Values, Raw and Minimizer sections until `end`, read with `limits`
+    fn scan_sections<R>(
+        inner: &mut R,
+        end: u64,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<Vec<(u8, u64)>>
+    where
+        R: std::io::BufRead + std::io::Seek,
+    {
+        let mut sections = Vec::new();
+        let mut values = section::Values::default();
+
+        loop {
+            let position = inner.stream_position()?;
+            if position >= end {
+                break;
+            }
+
+            let section_type = match next_prefix(inner, &mut values, limits)? {
+                Prefix::Values => b'v',
+                Prefix::Raw => {
+                    raw_section(&values, *limits)?.skip(inner)?;
+                    b'r'
+                }
+                Prefix::Minimizer => {
+                    minimizer_section(&values, *limits)?.skip(inner)?;
+                    b'm'
+                }
+                Prefix::Index => continue,
+                Prefix::End => return Err(error::Kff::NotASectionPrefix(b'K').into()),
+            };
+
+            sections.push((section_type, position));
+        }
+
+        Ok(sections)
+    }
+}
+
+/// Section found by [next_prefix]
+#[derive(
+    std::clone::Clone, std::marker::Copy, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+enum Prefix {
+    /// Values section, already read
+    Values,
+
+    /// Index section, already skipped
+    Index,
+
+    /// Raw section, only flag is read
+    Raw,
+
+    /// Minimizer section, only flag is read
+    Minimizer,
+
+    /// First byte of final signature
+    End,
+}
+
+/// Read flag of next section, a Values section is read in `values` and an Index section is skipped
+fn next_prefix<T>(
+    inner: &mut T,
+    values: &mut section::Values,
+    limits: &crate::ReadLimits,
+) -> error::Result<Prefix>
+where
+    T: std::io::Read + crate::KffRead,
+{
+    match inner.read_u8()? {
+        b'v' => {
+            *values = section::Values::read_with_limits(inner, limits)?;
+            Ok(Prefix::Values)
+        }
+        b'i' => {
+            section::Index::skip(inner)?;
+            Ok(Prefix::Index)
+        }
+        b'r' => Ok(Prefix::Raw),
+        b'm' => Ok(Prefix::Minimizer),
+        b'K' => Ok(Prefix::End),
+        e => Err(error::Kff::NotASectionPrefix(e).into()), // Any other value is an error
+    }
+}
+
+/// Build a Raw section from `values` with `limits`
+fn raw_section(values: &section::Values, limits: crate::ReadLimits) -> error::Result<section::Raw> {
+    let mut section = section::Raw::new(values)?;
+    section.set_limits(limits);
+
+    Ok(section)
+}
+
+/// Build a Minimizer section from `values` with `limits`
+fn minimizer_section(
+    values: &section::Values,
+    limits: crate::ReadLimits,
+) -> error::Result<section::Minimizer> {
+    let mut section = section::Minimizer::new(values)?;
+    section.set_limits(limits);
+
+    Ok(section)
+}
+
+/// Read footer that end at `end`, None if last section isn't a footer
+///
+/// Return footer, read with `limits`, without `footer_size` and its position.
+pub(crate) fn find_footer<F>(
+    inner: &mut F,
+    end: u64,
+    limits: &crate::ReadLimits,
+) -> error::Result<Option<(section::Values, u64)>>
+where
+    F: std::io::Read + std::io::Seek,
+{
+    let name_position = match end.checked_sub(8 + FOOTER_SIZE.len() as u64) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+
+    inner.seek(std::io::SeekFrom::Start(name_position))?;
+    let mut name = [0; FOOTER_SIZE.len()];
+    inner.read_exact(&mut name)?;
+    if name != FOOTER_SIZE {
+        return Ok(None);
+    }
+
+    let mut footer_size = [0; 8];
+    inner.read_exact(&mut footer_size)?;
+    let footer_size = u64::from_be_bytes(footer_size);
+    let position = match end.checked_sub(footer_size) {
+        Some(position) => position,
+        None => return Err(error::Kff::FooterSizeNotCorrect.into()),
+    };
+
+    inner.seek(std::io::SeekFrom::Start(position))?;
+    let mut reader = std::io::BufReader::new(std::io::Read::take(inner, footer_size));
+    if reader.read_u8()? != b'v' {
+        return Err(error::Kff::FooterSizeNotCorrect.into());
+    }
+    let mut footer = section::Values::read_with_limits(&mut reader, limits)?;
+    if footer.remove("footer_size") != Some(footer_size) {
+        return Err(error::Kff::FooterSizeNotCorrect.into());
+    }
+
+    Ok(Some((footer, position)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -381,6 +1142,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn validate() -> error::Result<()> {
+        let mut file = Kff::read(std::io::Cursor::new(KFF_FILE.to_vec()))?;
+        assert!(file.validate().is_ok());
+
+        let mut inner = KFF_FILE.to_vec();
+        inner.push(0);
+        let mut file = Kff::read(std::io::Cursor::new(inner))?;
+        assert!(matches!(
+            file.validate(),
+            Err(error::Error::Kff(error::Kff::DataAfterSignature))
+        ));
+
+        let mut inner = KFF_FILE.to_vec();
+        *inner.last_mut().unwrap() = b'K';
+        let mut file = Kff::read(std::io::Cursor::new(inner))?;
+        assert!(matches!(
+            file.validate(),
+            Err(error::Error::Kff(error::Kff::MissingMagic(_)))
+        ));
+
+        let mut file = Kff::read(std::io::Cursor::new(KFF_FILE[..30].to_vec()))?;
+        assert!(file.validate().is_err());
+
+        // Second call would check only remaining bytes
+        let mut file = Kff::read(std::io::Cursor::new(KFF_FILE.to_vec()))?;
+        file.validate()?;
+        assert!(matches!(
+            file.validate(),
+            Err(error::Error::Kff(error::Kff::SectionsAlreadyRead))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn load_footer() -> error::Result<()> {
         let inner_len = KFF_FILE.len();
@@ -389,11 +1185,14 @@ mod tests {
         let mut truth = section::Values::new();
         truth.insert("footer_size".to_string(), 29);
 
-        assert_eq!(Kff::load_footer(&mut inner)?, truth);
+        assert_eq!(
+            Kff::load_footer(&mut inner, &crate::ReadLimits::default())?,
+            truth
+        );
 
         inner.get_mut()[inner_len - 32] = b'f';
 
-        assert!(Kff::load_footer(&mut inner).is_err());
+        assert!(Kff::load_footer(&mut inner, &crate::ReadLimits::default()).is_err());
 
         Ok(())
     }
@@ -493,20 +1292,29 @@ mod tests {
         let mut t = std::fs::File::open(path)?;
         t.read_to_end(&mut inner)?;
 
+        // Values order depends on hash function
+        let mut values_bytes = Vec::new();
+        values.write(&mut values_bytes)?;
+
         assert_eq!(
-            inner,
+            inner[..13],
+            [b'K', b'F', b'F', 1, 0, 27, 1, 1, 0, 0, 0, 0, b'v']
+        );
+        assert_eq!(inner[13..13 + values_bytes.len()], values_bytes);
+        values_bytes.sort();
+        assert_eq!(
+            values_bytes,
+            vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 3, 5, 5, 95, 97, 97,
+                97, 100, 100, 100, 101, 101, 101, 105, 107, 109, 109, 111, 114, 114, 115, 116, 120,
+                122, 200
+            ]
+        );
+
+        assert_eq!(
+            inner[13 + values_bytes.len()..],
             vec![
-                b'K', b'F', b'F', //
-                1, 0,  // Version number
-                27, // Encoding
-                1, 1, // Uniq, Canonical
-                0, 0, 0, 0, // Free space size length
-                b'v', 0, 0, 0, 0, 0, 0, 0, 5, // Five values
-                b'm', 0, 0, 0, 0, 0, 0, 0, 0, 3, //
-                b'o', b'r', b'd', b'e', b'r', b'e', b'd', 0, 0, 0, 0, 0, 0, 0, 0, 0, //
-                b'k', 0, 0, 0, 0, 0, 0, 0, 0, 5, //
-                b'm', b'a', b'x', 0, 0, 0, 0, 0, 0, 0, 0, 200, //
-                b'd', b'a', b't', b'a', b'_', b's', b'i', b'z', b'e', 0, 0, 0, 0, 0, 0, 0, 0, 1,
                 b'r', 0, 0, 0, 0, 0, 0, 0, 3, // Three block
                 3, 27, 244, 1, 2, 3, // Three kmer in block
                 2, 27, 240, 1, 2, // Two kmer in block
@@ -528,4 +1336,587 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn write_with_index() -> error::Result<()> {
+        let header = section::Header::new(1, 0, 0b00011011, true, true, b"index".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 1);
+
+        writer.write_values(values.clone())?;
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[section::block::Block::new(
+                5,
+                1,
+                Kmer::from_ascii(b"ACTGAC", vec![1, 2], 0b00011011),
+                0,
+            )],
+        )?;
+        writer.finalize_with_index()?;
+
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(writer.into_inner()))?;
+
+        assert_eq!(
+            reader.index().as_ref().unwrap().pair(),
+            &vec![(b'v', 17), (b'r', 66)]
+        );
+
+        let kmers = reader.kmer_of_section(1)?;
+        assert_eq!(
+            kmers,
+            vec![
+                Kmer::from_ascii(b"ACTGA", vec![1], 0b00011011),
+                Kmer::from_ascii(b"CTGAC", vec![2], 0b00011011)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_with_checksum() -> error::Result<()> {
+        let write = |index: bool| -> error::Result<(Vec<u8>, usize)> {
+            let header = section::Header::new(1, 0, 0b00011011, true, true, b"".to_vec())?;
+            let mut writer = Kff::write_with_checksum(Vec::new(), header)?;
+
+            let mut values = section::Values::default();
+            values.insert("k".to_string(), 5);
+            values.insert("max".to_string(), 200);
+            values.insert("data_size".to_string(), 1);
+
+            writer.write_values(values.clone())?;
+            writer.write_raw(
+                section::Raw::new(&values)?,
+                &[section::block::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(b"ACTGAC", vec![1, 2], 0b00011011),
+                    0,
+                )],
+            )?;
+            let end_raw = writer.inner().len();
+
+            if index {
+                writer.finalize_with_index()?;
+            } else {
+                writer.finalize()?;
+            }
+
+            Ok((writer.into_inner(), end_raw))
+        };
+
+        for index in [false, true] {
+            let (mut inner, end_raw) = write(index)?;
+            // Footer follow index, an index of 2 sections use 35 bytes
+            let footer = if index { end_raw + 35 } else { end_raw };
+            let expected = utils::checksum::crc32c(&inner[..footer]) as u64;
+
+            let mut reader = Kff::read(std::io::Cursor::new(inner.clone()))?;
+            reader.validate()?;
+            assert_eq!(reader.values().get("crc32c"), Some(&expected));
+
+            // Checksum doesn't change reading
+            assert_eq!(
+                Kff::read(std::io::Cursor::new(inner.clone()))?
+                    .kmers()
+                    .count(),
+                2
+            );
+            if index {
+                let mut reader = Kff::read_with_index(std::io::Cursor::new(inner.clone()))?;
+                assert_eq!(reader.kmer_of_section(1)?.len(), 2);
+                assert!(matches!(
+                    reader.validate(),
+                    Err(error::Error::Kff(error::Kff::SectionsAlreadyRead))
+                ));
+            }
+
+            // Checksum can't be computed after a section was read
+            let mut reader = Kff::read(std::io::Cursor::new(inner.clone()))?;
+            assert!(reader.next_block_section().is_some());
+            assert!(matches!(
+                reader.validate(),
+                Err(error::Error::Kff(error::Kff::SectionsAlreadyRead))
+            ));
+
+            // Change canonical flag, it's still read as true
+            let mut flag = inner.clone();
+            flag[7] = 0x81;
+            let mut reader = if index {
+                Kff::read_with_index(std::io::Cursor::new(flag))?
+            } else {
+                Kff::read(std::io::Cursor::new(flag))?
+            };
+            assert!(reader.header().canonical_kmer());
+            assert!(matches!(
+                reader.validate(),
+                Err(error::Error::Kff(error::Kff::ChecksumMismatch(e, _))) if e == expected
+            ));
+
+            // Change data of last kmer
+            inner[end_raw - 1] = 3;
+            assert!(matches!(
+                Kff::read(std::io::Cursor::new(inner))?.validate(),
+                Err(error::Error::Kff(error::Kff::ChecksumMismatch(e, _))) if e == expected
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_after_footer() -> error::Result<()> {
+        let header = section::Header::new(1, 0, 0b00011011, true, true, b"".to_vec())?;
+        let mut writer = Kff::write_with_checksum(Vec::new(), header)?;
+
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 0);
+        writer.write_values(values.clone())?;
+        writer.write_footer(section::Values::default())?;
+        let len = writer.inner().len();
+
+        let block =
+            section::block::Block::new(5, 0, Kmer::from_ascii(b"ACTGA", vec![], 0b00011011), 0);
+        assert!(matches!(
+            writer.write_raw(section::Raw::new(&values)?, &[block]),
+            Err(error::Error::Kff(error::Kff::WriteAfterFooter))
+        ));
+        assert!(matches!(
+            writer.write_values(values),
+            Err(error::Error::Kff(error::Kff::WriteAfterFooter))
+        ));
+        assert!(matches!(
+            writer.finalize_with_index(),
+            Err(error::Error::Kff(error::Kff::WriteAfterFooter))
+        ));
+        assert_eq!(writer.inner().len(), len);
+
+        writer.finalize()?;
+        Kff::read(std::io::Cursor::new(writer.into_inner()))?.validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn open_append() -> error::Result<()> {
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 1);
+
+        fn write_batch<W>(
+            writer: &mut Kff<W>,
+            values: &section::Values,
+            seq: &[u8],
+        ) -> error::Result<()>
+        where
+            W: std::io::Write,
+        {
+            writer.write_values(values.clone())?;
+            writer.write_raw(
+                section::Raw::new(values)?,
+                &[section::block::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(seq, vec![1], 0b00011011),
+                    0,
+                )],
+            )
+        }
+
+        let kmers = |path: &std::path::Path| -> error::Result<Vec<Vec<u8>>> {
+            Kff::open(path)?
+                .kmers()
+                .map(|kmer| kmer.map(|kmer| kmer.seq(0b00011011)))
+                .collect()
+        };
+
+        for (index, checksum) in [(true, false), (false, false), (true, true)] {
+            let file = tempfile::NamedTempFile::new()?;
+            let header = section::Header::new(1, 0, 0b00011011, true, true, b"".to_vec())?;
+            let mut writer = if checksum {
+                Kff::write_with_checksum(
+                    std::io::BufWriter::new(std::fs::File::create(file.path())?),
+                    header,
+                )?
+            } else {
+                Kff::create(file.path(), header)?
+            };
+            write_batch(&mut writer, &values, b"AAAAA")?;
+            if index {
+                writer.finalize_with_index()?;
+            } else {
+                writer.finalize()?;
+            }
+            drop(writer);
+
+            // Append two batches
+            for seq in [b"CCCCC", b"GGGGG"] {
+                let mut writer = Kff::open_append(file.path())?;
+                write_batch(&mut writer, &values, seq)?;
+                writer.finalize_with_index()?;
+            }
+
+            assert_eq!(
+                kmers(file.path())?,
+                vec![b"AAAAA".to_vec(), b"CCCCC".to_vec(), b"GGGGG".to_vec()]
+            );
+            let mut reader = Kff::open(file.path())?;
+            reader.validate()?;
+            assert_eq!(reader.values().contains_key("crc32c"), checksum);
+
+            let mut reader = Kff::with_index(file.path())?;
+            assert_eq!(reader.index().as_ref().unwrap().pair().len(), 6);
+            assert_eq!(
+                reader.kmer_of_section(5)?,
+                vec![Kmer::from_ascii(b"GGGGG", vec![1], 0b00011011)]
+            );
+            assert_eq!(
+                reader.kmer_of_section(1)?,
+                vec![Kmer::from_ascii(b"AAAAA", vec![1], 0b00011011)]
+            );
+
+            // Writer dropped before finalize, file is unchanged
+            let before = std::fs::read(file.path())?;
+            let mut writer = Kff::open_append(file.path())?;
+            write_batch(&mut writer, &values, b"TTTTT")?;
+            writer.inner_mut().flush()?;
+            drop(writer);
+
+            assert_eq!(std::fs::read(file.path())?, before);
+            Kff::open(file.path())?.validate()?;
+
+            // Corrupted file isn't extended
+            if checksum {
+                let mut corrupted = before.clone();
+                corrupted[7] ^= 1;
+                std::fs::write(file.path(), &corrupted)?;
+
+                assert!(matches!(
+                    Kff::open_append(file.path()),
+                    Err(error::Error::Kff(error::Kff::ChecksumMismatch(_, _)))
+                ));
+                assert_eq!(std::fs::read(file.path())?, corrupted);
+            }
+        }
+
+        let file = tempfile::NamedTempFile::new()?;
+        std::fs::write(file.path(), &KFF_FILE[..KFF_FILE.len() - 1])?;
+        assert!(matches!(
+            Kff::open_append(file.path()),
+            Err(error::Error::Kff(error::Kff::MissingMagic(_)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn open_append_finalize() -> error::Result<()> {
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 1);
+
+        let block = |seq: &[u8]| {
+            section::block::Block::new(5, 1, Kmer::from_ascii(seq, vec![1], 0b00011011), 0)
+        };
+
+        let file = tempfile::NamedTempFile::new()?;
+        let header = section::Header::new(1, 0, 0b00011011, true, true, b"".to_vec())?;
+        let mut writer = Kff::create(file.path(), header)?;
+        writer.write_values(values.clone())?;
+        writer.write_raw(section::Raw::new(&values)?, &[block(b"AAAAA")])?;
+        writer.finalize_with_index()?;
+        drop(writer);
+
+        // Add a variable in footer
+        let mut editor = crate::tools::Editor::open(file.path())?;
+        let mut footer = editor.footer().cloned().unwrap();
+        footer.insert("nb_kmer".to_string(), 1);
+        editor.set_footer(footer)?;
+        drop(editor);
+
+        // Append without index, previous index is kept and chained
+        let mut writer = Kff::open_append(file.path())?;
+        writer.write_values(values.clone())?;
+        writer.write_raw(section::Raw::new(&values)?, &[block(b"CCCCC")])?;
+        writer.finalize()?;
+        drop(writer);
+
+        let mut reader = Kff::with_index(file.path())?;
+        assert_eq!(reader.index().as_ref().unwrap().pair().len(), 4);
+        assert_eq!(
+            reader.kmer_of_section(3)?,
+            vec![Kmer::from_ascii(b"CCCCC", vec![1], 0b00011011)]
+        );
+        let mut reader = Kff::open(file.path())?;
+        reader.validate()?;
+        assert_eq!(reader.values().get("nb_kmer"), Some(&1));
+        assert!(reader.values().contains_key("first_index"));
+
+        // Variables given to write_footer replace previous ones, previous index is still reachable
+        let mut writer = Kff::open_append(file.path())?;
+        let mut footer = section::Values::default();
+        footer.insert("nb_kmer".to_string(), 2);
+        writer.write_footer(footer)?;
+        writer.finalize()?;
+        drop(writer);
+
+        let mut reader = Kff::open(file.path())?;
+        reader.validate()?;
+        assert_eq!(reader.values().get("nb_kmer"), Some(&2));
+        assert_eq!(
+            Kff::with_index(file.path())?
+                .index()
+                .as_ref()
+                .unwrap()
+                .pair()
+                .len(),
+            4
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_check_values() -> error::Result<()> {
+        let header = section::Header::new(1, 0, 0b00011011, true, true, b"".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 1);
+
+        // No values section before
+        assert!(matches!(
+            writer.write_raw(section::Raw::new(&values)?, &[]),
+            Err(error::Error::Kff(error::Kff::FieldIsMissing(_)))
+        ));
+
+        writer.write_values(values.clone())?;
+        let position = writer.inner().len();
+
+        let mut raw = section::Raw::new(&values)?;
+        raw.set_k(4);
+        assert!(matches!(
+            writer.write_raw(raw, &[]),
+            Err(error::Error::Kff(error::Kff::SectionValuesMismatch(name, 5, 4))) if name == "k"
+        ));
+
+        assert!(matches!(
+            writer.write_raw(
+                section::Raw::new(&values)?,
+                &[section::block::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(b"ACTGAC", vec![1], 0b00011011),
+                    0,
+                )],
+            ),
+            Err(error::Error::Kff(error::Kff::BadDataLength(2, 1)))
+        ));
+
+        // Nothing is write after values
+        assert_eq!(writer.inner().len(), position);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_limits() -> error::Result<()> {
+        let header = section::Header::new(1, 0, 0b00011011, true, true, b"free".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone())?;
+
+        let block = |seq: &[u8], data: Vec<u8>| {
+            section::block::Block::new(5, 1, Kmer::from_ascii(seq, data, 0b00011011), 0)
+        };
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[block(b"ACTGAC", vec![1, 2]), block(b"ACTGA", vec![3])],
+        )?;
+        writer.finalize()?;
+        let buffer = writer.into_inner();
+
+        let read = |limits: crate::ReadLimits| -> error::Result<Vec<Kmer>> {
+            Kff::read_with_limits(std::io::Cursor::new(buffer.clone()), limits)?
+                .kmers()
+                .collect()
+        };
+        let limit = |name: &str, result: error::Result<Vec<Kmer>>| matches!(result, Err(error::Error::Kff(error::Kff::LimitExceeded(n, _, _))) if n == name);
+
+        assert_eq!(read(crate::ReadLimits::default())?.len(), 3);
+        assert_eq!(read(crate::ReadLimits::unlimited())?.len(), 3);
+
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_free_block(3);
+        assert!(limit("free block size", read(limits)));
+
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_variables(2);
+        assert!(limit("number of variables", read(limits)));
+
+        // First block has 6 nucleotides in 2 bytes and 2 bytes of data
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_block_length(3);
+        assert!(limit("block length", read(limits)));
+        limits.set_max_block_length(4);
+        assert_eq!(read(limits)?.len(), 3);
+
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_section_size(8);
+        assert!(limit("section size", read(limits)));
+
+        // Limits can be change after header is read
+        let mut reader = Kff::read(std::io::Cursor::new(buffer.clone()))?;
+        reader.set_limits(limits);
+        assert!(limit(
+            "section size",
+            reader.next_section_bytes().unwrap().map(|_| Vec::new())
+        ));
+
+        // Sections skipped without decoding are checked too
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_block_length(3);
+        let mut reader = Kff::read_with_limits(std::io::Cursor::new(buffer.clone()), limits)?;
+        assert!(limit(
+            "block length",
+            reader.next_section_bytes().unwrap().map(|_| Vec::new())
+        ));
+
+        let mut reader = Kff::read(std::io::Cursor::new(buffer))?;
+        assert_eq!(reader.next_section_bytes().unwrap()?.1.len(), 8 + 9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_with_index_limits() -> error::Result<()> {
+        let header = section::Header::new(1, 0, 0b00011011, true, true, b"free".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 200);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone())?;
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[section::block::Block::new(
+                5,
+                1,
+                Kmer::from_ascii(b"ACTGAC", vec![1, 2], 0b00011011),
+                0,
+            )],
+        )?;
+        writer.finalize_with_index()?;
+        let buffer = writer.into_inner();
+
+        let read = |limits: crate::ReadLimits| -> error::Result<Vec<Kmer>> {
+            Kff::read_with_index_and_limits(std::io::Cursor::new(buffer.clone()), limits)?
+                .kmer_of_section(1)
+        };
+        let limit = |name: &str, result: error::Result<Vec<Kmer>>| matches!(result, Err(error::Error::Kff(error::Kff::LimitExceeded(n, _, _))) if n == name);
+
+        assert_eq!(read(crate::ReadLimits::default())?.len(), 2);
+
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_free_block(3);
+        assert!(limit("free block size", read(limits)));
+
+        // Footer contains first_index and footer_size
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_variables(1);
+        assert!(limit("number of variables", read(limits)));
+
+        // Index of two sections
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_section_size(17);
+        assert!(limit("index size", read(limits)));
+
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_block_length(3);
+        assert!(limit("block length", read(limits)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sections_with_minimizer() -> error::Result<()> {
+        let encoding = 0b00011011;
+        let header = section::Header::new(1, 0, encoding, true, false, b"".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::default();
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), 1);
+
+        let write = |writer: &mut Kff<Vec<u8>>, minimizer: &[u8], kmers: &[(&[u8], u8)]| {
+            let blocks: Vec<section::Block> = kmers
+                .iter()
+                .map(|(seq, data)| {
+                    let kmer = Kmer::from_ascii(seq, vec![*data], encoding);
+                    let (offset, _) = crate::kmer::minimizer(kmer.seq2bit(), 2).unwrap();
+                    section::Block::new(5, 1, kmer, offset)
+                })
+                .collect();
+
+            writer.write_minimizer(
+                section::Minimizer::new(&values)?,
+                crate::kmer::seq2bits(minimizer, encoding),
+                &blocks,
+            )
+        };
+
+        writer.write_values(values.clone())?;
+        write(&mut writer, b"AC", &[(b"ACTGT", 1), (b"TTACG", 2)])?;
+        write(&mut writer, b"AG", &[(b"GAGGT", 3)])?;
+        writer.write_values(values.clone())?;
+        write(&mut writer, b"AC", &[(b"GTACT", 4)])?;
+        writer.finalize_with_index()?;
+
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(writer.into_inner()))?;
+
+        let kmers = reader.sections_with_minimizer(&crate::kmer::seq2bits(b"AC", encoding))?;
+        let seqs: Vec<Vec<u8>> = kmers.iter().map(|k| k.seq(encoding)).collect();
+        assert_eq!(
+            seqs,
+            vec![b"ACTGT".to_vec(), b"TTACG".to_vec(), b"GTACT".to_vec()]
+        );
+
+        assert!(reader
+            .sections_with_minimizer(&crate::kmer::seq2bits(b"TT", encoding))?
+            .is_empty());
+
+        // Membership of a query kmer
+        let query = crate::kmer::seq2bits(b"GAGGT", encoding);
+        let (_, minimizer) = crate::kmer::minimizer(&query, 2)?;
+        let found = reader
+            .sections_with_minimizer(&minimizer)?
+            .into_iter()
+            .find(|k| k.seq2bit() == &query);
+        assert_eq!(found.map(|k| k.data().clone()), Some(vec![3]));
+
+        let mut reader = Kff::read(std::io::Cursor::new(KFF_FILE.to_vec()))?;
+        assert!(reader
+            .sections_with_minimizer(&crate::kmer::seq2bits(b"AC", encoding))
+            .is_err());
+
+        Ok(())
+    }
 }
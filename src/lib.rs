@@ -14,6 +14,7 @@ pub mod error;
 pub mod iterator;
 pub mod kff;
 pub mod section;
+pub mod tools;
 pub mod utils;
 
 pub use self::kff::Kff;
@@ -0,0 +1,587 @@
+//! Command line tools to manipulate KFF files
+
+/* std use */
+use std::io::Write as _;
+
+/* crate use */
+use clap::Parser as _;
+
+/* project use */
+use kff::error;
+use kff::tools;
+
+/// Manipulate Kmer File Format files
+#[derive(clap::Parser, std::fmt::Debug)]
+#[clap(name = "kff", version, author = "Pierre Marijon <pierre@marijon.fr>")]
+pub struct Command {
+    /// Silence all output
+    #[clap(short = 'q', long = "quiet")]
+    pub quiet: bool,
+
+    /// Verbose mode (-v, -vv, -vvv, etc)
+    #[clap(short = 'v', long = "verbosity", action = clap::ArgAction::Count)]
+    pub verbosity: u8,
+
+    /// Timestamp (sec, ms, ns, none)
+    #[clap(short = 'T', long = "timestamp")]
+    pub ts: Option<stderrlog::Timestamp>,
+
+    /// Subcommand
+    #[clap(subcommand)]
+    pub subcommand: SubCommand,
+}
+
+/// Available subcommands
+#[derive(clap::Subcommand, std::fmt::Debug)]
+pub enum SubCommand {
+    /// Print header and list of sections
+    Info(Input),
+
+    /// Decode all sections and check final signature
+    Validate(Input),
+
+    /// Write kmers and data in text
+    View(View),
+
+    /// Write each block, or each maximal unitig, as a FASTA record
+    ToFasta(ToFasta),
+
+    /// Convert a text file, one kmer and optionally a count per line, in KFF
+    FromText(FromText),
+
+    /// Count kmers of a FASTA or FASTQ file
+    FromReads(FromReads),
+
+    /// Convert a binary KMC database in KFF
+    FromKmc(FromKmc),
+
+    /// Search kmers of each read of a FASTA or FASTQ file in an indexed KFF file
+    Query(Query),
+
+    /// Concatenate KFF files with same encoding, uniq and canonical flags
+    Merge(Merge),
+
+    /// Split a KFF file in many KFF files
+    Split(Split),
+
+    /// Rewrite a KFF file with an index and a footer
+    Index(InputOutput),
+
+    /// Rewrite all decodable sections of a truncated KFF file with an index, a footer and final signature
+    Repair(InputOutput),
+
+    /// Sort kmers, all kmers are kept in memory
+    Sort(InputOutput),
+
+    /// Print statistics in json
+    Stats(InputOutput),
+}
+
+/// Input argument
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct Input {
+    /// Kff input file, stdin if not set or '-'
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<std::path::PathBuf>,
+}
+
+/// Input and output arguments
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct InputOutput {
+    /// Input file, stdin if not set or '-'
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<std::path::PathBuf>,
+
+    /// Output file, stdout if not set or '-'
+    #[clap(short = 'o', long = "output")]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Arguments of view subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct View {
+    #[clap(flatten)]
+    pub io: InputOutput,
+
+    /// Text format, 'tabulated' like kmc_dump, 'column' or 'fasta' like jellyfish dump
+    #[clap(short = 'f', long = "format", default_value = "tabulated", value_parser = FORMATS)]
+    pub format: String,
+}
+
+/// Arguments of to-fasta subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct ToFasta {
+    #[clap(flatten)]
+    pub io: InputOutput,
+
+    /// Assemble kmers in maximal unitigs, all kmers are kept in memory
+    #[clap(short = 'u', long = "unitigs")]
+    pub unitigs: bool,
+}
+
+/// Arguments of from-text subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct FromText {
+    #[clap(flatten)]
+    pub io: InputOutput,
+
+    /// Text format, 'tabulated' like kmc_dump, 'column' or 'fasta' like jellyfish dump
+    #[clap(short = 'f', long = "format", default_value = "tabulated", value_parser = FORMATS)]
+    pub format: String,
+
+    /// Number of bytes used to store count of each kmer
+    #[clap(short = 'd', long = "data-size", default_value = "1")]
+    pub data_size: usize,
+
+    /// Encoding of nucleotides A, C, T, G, default is 0b00011110
+    #[clap(short = 'e', long = "encoding", default_value = "30")]
+    pub encoding: u8,
+
+    /// Each kmer is present only once
+    #[clap(short = 'u', long = "uniq")]
+    pub uniq: bool,
+
+    /// Kmers are canonical
+    #[clap(short = 'c', long = "canonical")]
+    pub canonical: bool,
+}
+
+/// Arguments of from-reads subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct FromReads {
+    #[clap(flatten)]
+    pub io: InputOutput,
+
+    /// Size of kmer
+    #[clap(short = 'k', long = "kmer-size")]
+    pub k: u64,
+
+    /// Size of minimizer, if set kmers are written in Minimizer sections
+    #[clap(short = 'm', long = "minimizer-size")]
+    pub m: Option<u64>,
+
+    /// Maximal number of kmer per block
+    #[clap(short = 'M', long = "max", default_value = "255")]
+    pub max: u64,
+
+    /// Number of bytes used to store count of each kmer
+    #[clap(short = 'd', long = "data-size", default_value = "1")]
+    pub data_size: usize,
+
+    /// Encoding of nucleotides A, C, T, G, default is 0b00011110
+    #[clap(short = 'e', long = "encoding", default_value = "30")]
+    pub encoding: u8,
+
+    /// Count canonical kmers
+    #[clap(short = 'c', long = "canonical")]
+    pub canonical: bool,
+
+    /// Count with sorted temporary files in this directory instead of a hash map
+    #[clap(short = 't', long = "temporary")]
+    pub temporary: Option<std::path::PathBuf>,
+
+    /// Number of kmers kept in memory before write a temporary file
+    #[clap(short = 'b', long = "buffer", default_value = "10000000")]
+    pub buffer: usize,
+}
+
+/// Arguments of from-kmc subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct FromKmc {
+    /// KMC database, path without '.kmc_pre' and '.kmc_suf' extension
+    #[clap(short = 'i', long = "input")]
+    pub input: std::path::PathBuf,
+
+    /// Output file, stdout if not set or '-'
+    #[clap(short = 'o', long = "output")]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Arguments of query subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct Query {
+    /// Kff input file with an index
+    #[clap(short = 'i', long = "input")]
+    pub input: std::path::PathBuf,
+
+    /// FASTA or FASTQ file, stdin if not set or '-'
+    #[clap(short = 'r', long = "reads")]
+    pub reads: Option<std::path::PathBuf>,
+
+    /// Output file, stdout if not set or '-'
+    #[clap(short = 'o', long = "output")]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Arguments of merge subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+pub struct Merge {
+    /// Kff input files
+    #[clap(short = 'i', long = "inputs", required = true, num_args = 1..)]
+    pub inputs: Vec<std::path::PathBuf>,
+
+    /// Output file, stdout if not set or '-'
+    #[clap(short = 'o', long = "output")]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Arguments of split subcommand
+#[derive(clap::Args, std::fmt::Debug)]
+#[clap(group(clap::ArgGroup::new("strategy").required(true)))]
+pub struct Split {
+    /// Input file, stdin if not set or '-'
+    #[clap(short = 'i', long = "input")]
+    pub input: Option<std::path::PathBuf>,
+
+    /// Prefix of output files, shard number and '.kff' are append
+    #[clap(short = 'p', long = "prefix")]
+    pub prefix: String,
+
+    /// Split by hash of minimizer in this number of shards
+    #[clap(short = 'm', long = "minimizer", group = "strategy")]
+    pub minimizer: Option<usize>,
+
+    /// Maximal number of kmer sections per shard
+    #[clap(short = 's', long = "sections", group = "strategy")]
+    pub sections: Option<u64>,
+
+    /// Target number of bytes of kmer sections per shard
+    #[clap(short = 'b', long = "size", group = "strategy")]
+    pub size: Option<u64>,
+}
+
+const FORMATS: [&str; 3] = ["tabulated", "column", "fasta"];
+
+type Reader = Box<dyn std::io::BufRead>;
+type Writer = Box<dyn std::io::Write>;
+
+fn main() -> error::Result<()> {
+    // parse cli
+    let params = Command::parse();
+
+    // Setup logger
+    stderrlog::new()
+        .quiet(params.quiet)
+        .verbosity(params.verbosity as usize)
+        .timestamp(params.ts.unwrap_or(stderrlog::Timestamp::Off))
+        .init()
+        .unwrap();
+
+    match params.subcommand {
+        SubCommand::Info(args) => info(open(args.input)?, create(None)?),
+        SubCommand::Validate(args) => {
+            open(args.input)?.validate()?;
+            log::info!("File is valid");
+
+            Ok(())
+        }
+        SubCommand::View(args) => {
+            let nb_kmer = tools::to_text(
+                open(args.io.input)?,
+                create(args.io.output)?,
+                format(&args.format),
+            )?;
+            log::info!("{} kmers write", nb_kmer);
+
+            Ok(())
+        }
+        SubCommand::ToFasta(args) => {
+            let mode = if args.unitigs {
+                tools::fasta::Mode::Unitigs
+            } else {
+                tools::fasta::Mode::Blocks
+            };
+
+            let nb_record = tools::to_fasta(open(args.io.input)?, create(args.io.output)?, mode)?;
+            log::info!("{} records write", nb_record);
+
+            Ok(())
+        }
+        SubCommand::FromText(args) => {
+            let header = kff::section::Header::new(
+                1,
+                0,
+                args.encoding,
+                args.uniq,
+                args.canonical,
+                free_block("kff from-text")?,
+            )?;
+
+            let nb_kmer = tools::from_text(
+                input(args.io.input)?,
+                create(args.io.output)?,
+                header,
+                format(&args.format),
+                args.data_size,
+            )?;
+            log::info!("{} kmers write", nb_kmer);
+
+            Ok(())
+        }
+        SubCommand::FromReads(args) => {
+            let header = kff::section::Header::new(
+                1,
+                0,
+                args.encoding,
+                true,
+                args.canonical,
+                free_block("kff from-reads")?,
+            )?;
+
+            let mut parameters = tools::from_reads::Parameters::new(args.k);
+            parameters
+                .set_m(args.m)
+                .set_max(args.max)
+                .set_data_size(args.data_size);
+            if let Some(directory) = args.temporary {
+                parameters.set_storage(tools::from_reads::Storage::Sorted {
+                    buffer: args.buffer,
+                    directory,
+                });
+            }
+
+            let nb_kmer = tools::from_reads(
+                input(args.io.input)?,
+                create(args.io.output)?,
+                header,
+                &parameters,
+            )?;
+            log::info!("{} distinct kmers write", nb_kmer);
+
+            Ok(())
+        }
+        SubCommand::FromKmc(args) => {
+            let nb_kmer = tools::from_kmc(args.input, create(args.output)?)?;
+            log::info!("{} kmers write", nb_kmer);
+
+            Ok(())
+        }
+        SubCommand::Query(args) => query(
+            kff::Kff::with_index(args.input)?,
+            input(args.reads)?,
+            create(args.output)?,
+        ),
+        SubCommand::Merge(args) => {
+            let inputs = args
+                .inputs
+                .into_iter()
+                .map(|path| open(Some(path)))
+                .collect::<error::Result<Vec<_>>>()?;
+
+            tools::concat(inputs, create(args.output)?)
+        }
+        SubCommand::Split(args) => {
+            let strategy = match (args.minimizer, args.sections, args.size) {
+                (Some(nb_shard), _, _) => tools::split::Strategy::Minimizer(nb_shard),
+                (_, Some(nb_section), _) => tools::split::Strategy::Sections(nb_section),
+                (_, _, Some(size)) => tools::split::Strategy::Size(size),
+                _ => unreachable!("clap require one strategy"),
+            };
+
+            let shards = tools::split(open(args.input)?, strategy, |i| {
+                create(Some(format!("{}{}.kff", args.prefix, i).into()))
+            })?;
+            log::info!("{} shards write", shards.len());
+
+            Ok(())
+        }
+        SubCommand::Index(args) => tools::concat([open(args.input)?], create(args.output)?),
+        SubCommand::Repair(args) => {
+            match tools::Recovery::new(input(args.input)?)?.repair(create(args.output)?)? {
+                Some(truncation) => log::warn!(
+                    "Input truncated at byte {}: {}",
+                    truncation.position(),
+                    truncation.error()
+                ),
+                None => log::info!("Input is complete"),
+            }
+
+            Ok(())
+        }
+        SubCommand::Sort(args) => {
+            let nb_kmer = tools::sort(open(args.input)?, create(args.output)?)?;
+            log::info!("{} kmers write", nb_kmer);
+
+            Ok(())
+        }
+        SubCommand::Stats(args) => {
+            let stats = open(args.input)?.stats()?;
+
+            let mut output = create(args.output)?;
+            writeln!(
+                output,
+                "{}",
+                serde_json::to_string_pretty(&stats).map_err(std::io::Error::from)?
+            )?;
+            output.flush()?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Write for each read its number, number of kmers found, number of kmers and presence of each kmer
+fn query(
+    mut kff: kff::Kff<std::io::BufReader<std::fs::File>>,
+    reads: Reader,
+    mut output: Writer,
+) -> error::Result<()> {
+    let reads = tools::from_reads::Records::new(reads).collect::<error::Result<Vec<_>>>()?;
+
+    for (i, coverage) in tools::query(&mut kff, &reads)?.into_iter().enumerate() {
+        let presence: String = coverage
+            .iter()
+            .map(|data| if data.is_some() { '1' } else { '0' })
+            .collect();
+
+        writeln!(
+            output,
+            "{}\t{}\t{}\t{}",
+            i,
+            coverage.iter().filter(|data| data.is_some()).count(),
+            coverage.len(),
+            presence
+        )?;
+    }
+    output.flush()?;
+
+    Ok(())
+}
+
+/// Print header, values and kmer sections of input without decoding kmers
+fn info(mut input: kff::Kff<Reader>, mut output: Writer) -> error::Result<()> {
+    let header = input.header().clone();
+    writeln!(
+        output,
+        "version: {}.{}",
+        header.major_version(),
+        header.minor_version()
+    )?;
+    writeln!(output, "encoding: {:#010b}", header.encoding())?;
+    writeln!(output, "uniq: {}", header.uniq_kmer())?;
+    writeln!(output, "canonical: {}", header.canonical_kmer())?;
+    writeln!(
+        output,
+        "free block: {}",
+        String::from_utf8_lossy(header.free_block())
+    )?;
+
+    let mut last_values = None;
+    let mut nb_section = 0;
+    while let Some(section) = input.next_section_bytes() {
+        let (section_type, bytes) = section?;
+
+        if last_values.as_ref() != Some(input.values()) {
+            let mut values: Vec<_> = input.values().iter().collect();
+            values.sort();
+
+            let values: Vec<String> = values
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            writeln!(output, "values: {}", values.join(" "))?;
+
+            last_values = Some(input.values().clone());
+        }
+
+        let section_type = if section_type == b'r' {
+            "raw"
+        } else {
+            "minimizer"
+        };
+        writeln!(
+            output,
+            "section {}: {} {} bytes",
+            nb_section,
+            section_type,
+            bytes.len() + 1
+        )?;
+        nb_section += 1;
+    }
+
+    output.flush()?;
+
+    Ok(())
+}
+
+/// Build header free block with producer, version, command line and date metadata
+fn free_block(producer: &str) -> error::Result<Vec<u8>> {
+    let mut metadata = kff::section::Metadata::new();
+    metadata
+        .insert(kff::section::metadata::PRODUCER, producer)?
+        .insert(kff::section::metadata::VERSION, env!("CARGO_PKG_VERSION"))?
+        .insert(
+            kff::section::metadata::COMMAND,
+            &command_line(std::env::args_os()),
+        )?
+        .insert(kff::section::metadata::DATE, &today())?;
+
+    Ok(metadata.to_bytes())
+}
+
+/// Current UTC date as `YYYY-MM-DD`
+fn today() -> String {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or(0);
+
+    // Days since 0000-03-01, years begin in March so leap day is last day of year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_of_year = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_of_year + 2) / 5 + 1;
+    let month = (month_of_year + 2) % 12 + 1;
+    let year = era * 400 + year_of_era + (month <= 2) as u64;
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Join arguments in one line, non-UTF-8 bytes are replaced and line breaks become spaces
+fn command_line<I>(args: I) -> String
+where
+    I: std::iter::Iterator<Item = std::ffi::OsString>,
+{
+    args.map(|arg| arg.to_string_lossy().replace(['\n', '\r'], " "))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Convert name of text format, names are checked by clap
+fn format(name: &str) -> tools::text::Format {
+    match name {
+        "column" => tools::text::Format::Column,
+        "fasta" => tools::text::Format::Fasta,
+        _ => tools::text::Format::Tabulated,
+    }
+}
+
+/// Open a Kff reader on path, stdin if path is None or '-'
+fn open(path: Option<std::path::PathBuf>) -> error::Result<kff::Kff<Reader>> {
+    kff::Kff::read(input(path)?)
+}
+
+/// Get a buffered readable on path, stdin if path is None or '-'
+fn input(path: Option<std::path::PathBuf>) -> error::Result<Reader> {
+    match path {
+        Some(path) if path.as_os_str() != "-" => Ok(Box::new(std::io::BufReader::new(
+            std::fs::File::open(path)?,
+        ))),
+        _ => Ok(Box::new(std::io::stdin().lock())),
+    }
+}
+
+/// Get a buffered writable on path, stdout if path is None or '-'
+fn create(path: Option<std::path::PathBuf>) -> error::Result<Writer> {
+    match path {
+        Some(path) if path.as_os_str() != "-" => Ok(Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path)?,
+        ))),
+        _ => Ok(Box::new(std::io::BufWriter::new(std::io::stdout().lock()))),
+    }
+}
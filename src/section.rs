@@ -5,11 +5,13 @@
 /* crate use */
 
 /* project use */
+use crate::Seq2Bit;
 
 /* mod declaration */
 pub mod block;
 pub mod header;
 pub mod index;
+pub mod metadata;
 pub mod minimizer;
 pub mod raw;
 pub mod values;
@@ -18,6 +20,57 @@ pub mod values;
 pub use block::Block;
 pub use header::Header;
 pub use index::Index;
+pub use metadata::Metadata;
 pub use minimizer::Minimizer;
 pub use raw::Raw;
 pub use values::Values;
+
+/// Blocks of a kmer section, with minimizer if section is a Minimizer section
+#[derive(std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq)]
+pub enum KmerSection {
+    /// Blocks of a Raw section
+    Raw(Vec<Block>),
+
+    /// Minimizer and blocks of a Minimizer section
+    Minimizer(Seq2Bit, Vec<Block>),
+}
+
+impl KmerSection {
+    /// Get blocks of section
+    pub fn blocks(&self) -> &[Block] {
+        match self {
+            KmerSection::Raw(blocks) => blocks,
+            KmerSection::Minimizer(_, blocks) => blocks,
+        }
+    }
+
+    /// Get minimizer of section, None for a Raw section
+    pub fn minimizer(&self) -> Option<&Seq2Bit> {
+        match self {
+            KmerSection::Raw(_) => None,
+            KmerSection::Minimizer(minimizer, _) => Some(minimizer),
+        }
+    }
+
+    /// Number of bytes need to store this section, section flag include
+    pub fn size(&self, max: u64) -> u64 {
+        match self {
+            KmerSection::Raw(blocks) => 1 + 8 + blocks.iter().map(|b| b.raw_size(max)).sum::<u64>(),
+            KmerSection::Minimizer(minimizer, blocks) => {
+                let m = (minimizer.len() / 2) as u64;
+
+                1 + crate::bytes2store_k(m)
+                    + 8
+                    + blocks.iter().map(|b| b.minimizer_size(m, max)).sum::<u64>()
+            }
+        }
+    }
+
+    /// Consume section to get blocks
+    pub fn into_blocks(self) -> Vec<Block> {
+        match self {
+            KmerSection::Raw(blocks) => blocks,
+            KmerSection::Minimizer(_, blocks) => blocks,
+        }
+    }
+}
@@ -10,7 +10,14 @@ use crate::kmer::Seq2Bit;
 use crate::Kmer;
 
 /// Struct to data present in KFF Raw or Minimizer block
-#[derive(getset::Getters, std::fmt::Debug, std::default::Default)]
+#[derive(
+    getset::Getters,
+    std::clone::Clone,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+    std::default::Default,
+)]
 #[getset(get = "pub")]
 pub struct Block {
     /// Size of kmer
@@ -42,22 +49,121 @@ impl Block {
         }
     }
 
+    /// Create a new block, minimizer offset is the first occurrence of `minimizer` present in all kmers of block
+    pub fn new_with_minimizer(
+        k: u64,
+        data_size: usize,
+        kmer: Kmer,
+        minimizer: &Seq2Bit,
+    ) -> error::Result<Self> {
+        let length = kmer.seq2bit().len() / 2;
+        let m = minimizer.len() / 2;
+        if length < k as usize || m > k as usize {
+            return Err(error::Kff::MinimizerNotInBlock.into());
+        }
+
+        // Minimizer must begin after start of last kmer and end before end of first kmer
+        let minimizer_offset = (length - k as usize..=k as usize - m)
+            .find(|offset| kmer.seq2bit()[offset * 2..(offset + m) * 2] == minimizer[..])
+            .ok_or(error::Kff::MinimizerNotInBlock)?;
+
+        Ok(Self::new(k, data_size, kmer, minimizer_offset))
+    }
+
+    /// Number of kmer in this block
+    pub fn nb_kmer(&self) -> u64 {
+        (self.kmer.seq2bit().len() / 2 + 1).saturating_sub(self.k as usize) as u64
+    }
+
+    /// Number of bytes need to store this block in a Raw section
+    pub fn raw_size(&self, max: u64) -> u64 {
+        block_nb_kmer_size(max)
+            + crate::bytes2store_k((self.kmer.seq2bit().len() / 2) as u64)
+            + self.kmer.data().len() as u64
+    }
+
+    /// Number of bytes need to store this block in a Minimizer section
+    pub fn minimizer_size(&self, m: u64, max: u64) -> u64 {
+        block_nb_kmer_size(max)
+            + nb_kmer_size(max_offset(self.k, max))
+            + crate::bytes2store_k(((self.kmer.seq2bit().len() / 2) as u64).saturating_sub(m))
+            + self.kmer.data().len() as u64
+    }
+
+    /// Check block can be write in a section with this `k`, `data_size` and `max`, and `minimizer` for a Minimizer section
+    ///
+    /// Block must have kmer size `k`, between 1 and `max` kmers, `data_size` bytes of data per
+    /// kmer and, if `minimizer` is set, minimizer at `minimizer_offset`.
+    pub fn validate(
+        &self,
+        k: u64,
+        data_size: u64,
+        max: u64,
+        minimizer: Option<&Seq2Bit>,
+    ) -> error::Result<()> {
+        check_parameters(k, minimizer.map_or(0, |m| m.len() as u64 / 2), max)?;
+
+        if self.k != k {
+            return Err(error::Kff::KmerSizeMismatch(k, self.k).into());
+        }
+
+        let length = (self.kmer.seq2bit().len() / 2) as u64;
+        if length < k {
+            return Err(error::Kff::BlockTooShort(length, k).into());
+        }
+
+        let nb_kmer = self.nb_kmer();
+        if nb_kmer > max {
+            return Err(error::Kff::BlockTooLong(nb_kmer, max).into());
+        }
+
+        let data_length = self.kmer.data().len() as u64;
+        let expected = nb_kmer
+            .checked_mul(data_size)
+            .ok_or(error::Kff::ValueOverflow(nb_kmer, data_size))?;
+        if data_length != expected {
+            return Err(error::Kff::BadDataLength(expected, data_length).into());
+        }
+
+        if let Some(minimizer) = minimizer {
+            let range = self.minimizer_offset * 2..self.minimizer_offset * 2 + minimizer.len();
+            if self.kmer.seq2bit().get(range) != Some(&minimizer[..]) {
+                return Err(error::Kff::MinimizerNotFound(self.minimizer_offset as u64).into());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Read raw block
     pub fn read_raw<R>(inner: &mut R, k: u64, data_size: usize, max: u64) -> error::Result<Self>
     where
         R: std::io::Read + crate::KffRead,
     {
-        let nb_kmer = if max <= 1 {
-            1
-        } else {
-            read_nb_kmer(inner, max)? as usize
-        };
+        Self::read_raw_with_limits(inner, k, data_size, max, &crate::ReadLimits::default())
+    }
+
+    /// Read raw block, size of block is check against `limits`
+    pub fn read_raw_with_limits<R>(
+        inner: &mut R,
+        k: u64,
+        data_size: usize,
+        max: u64,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        check_parameters(k, 0, max)?;
+
+        let nb_kmer = read_block_nb_kmer(inner, max)?;
+        let length = nb_nucleotide(nb_kmer, k, 0)?;
+        let data_length = data_length(nb_kmer, data_size)?;
+        check_block_length(length, data_length, limits)?;
 
-        let kmer = inner
-            .read_2bits(nb_kmer + k as usize - 1)?
-            .into_boxed_bitslice();
+        let kmer = inner.read_2bits(length)?.into_boxed_bitslice();
 
-        let data = inner.read_n_bytes_dyn(nb_kmer * data_size)?;
+        let data = inner.read_n_bytes_dyn(data_length)?;
 
         Ok(Self {
             k,
@@ -68,18 +174,47 @@ impl Block {
         })
     }
 
+    /// Skip raw block, return number of kmer in block
+    pub fn skip_raw<R>(inner: &mut R, k: u64, data_size: usize, max: u64) -> error::Result<u64>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        Self::skip_raw_with_limits(inner, k, data_size, max, &crate::ReadLimits::default())
+            .map(|(nb_kmer, _)| nb_kmer)
+    }
+
+    /// Skip raw block, size of block is check against `limits`, return number of kmer and size in bytes of block
+    pub fn skip_raw_with_limits<R>(
+        inner: &mut R,
+        k: u64,
+        data_size: usize,
+        max: u64,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<(u64, u64)>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        check_parameters(k, 0, max)?;
+
+        let nb_kmer = read_block_nb_kmer(inner, max)?;
+        let length = nb_nucleotide(nb_kmer, k, 0)?;
+        let data_length = data_length(nb_kmer, data_size)?;
+        check_block_length(length, data_length, limits)?;
+
+        let size = (crate::bytes2store_k(length as u64) as usize)
+            .checked_add(data_length)
+            .ok_or(error::Kff::ValueOverflow(nb_kmer, data_size as u64))?;
+        inner.skip_n_bytes(size)?;
+
+        Ok((nb_kmer, block_nb_kmer_size(max) + size as u64))
+    }
+
     /// Write raw block
     pub fn write_raw<W>(&self, outer: &mut W, max: u64) -> error::Result<()>
     where
         W: std::io::Write + crate::KffWrite,
     {
-        if max > 1 {
-            write_nb_kmer(
-                outer,
-                max,
-                (self.kmer.seq2bit().len() / 2 - self.k as usize + 1) as u64,
-            )?;
-        }
+        write_block_nb_kmer(outer, max, self.nb_kmer())?;
         outer.write_bytes(self.kmer.seq2bit().as_raw_slice())?;
         outer.write_bytes(self.kmer.data().as_slice())?;
 
@@ -98,23 +233,53 @@ impl Block {
     where
         R: std::io::Read + crate::KffRead,
     {
-        let nb_kmer = if max <= 1 {
-            1
-        } else {
-            read_nb_kmer(inner, max)? as usize
-        };
+        Self::read_minimizer_with_limits(
+            inner,
+            k,
+            m,
+            data_size,
+            max,
+            minimizer,
+            &crate::ReadLimits::default(),
+        )
+    }
 
-        let minimizer_offset = read_nb_kmer(inner, std::cmp::min(k + max - 1, u64::MAX))? as usize;
+    /// Read minimizer block, size of block is check against `limits`
+    pub fn read_minimizer_with_limits<R>(
+        inner: &mut R,
+        k: u64,
+        m: u64,
+        data_size: usize,
+        max: u64,
+        minimizer: &Seq2Bit,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        check_parameters(k, m, max)?;
 
-        let kmer_without_minimizer = inner.read_2bits(nb_kmer + k as usize - 1 - m as usize)?;
+        let nb_kmer = read_block_nb_kmer(inner, max)?;
+        let length = nb_nucleotide(nb_kmer, k, m)?;
+        let data_length = data_length(nb_kmer, data_size)?;
+        check_block_length(length, data_length, limits)?;
 
-        let mut kmer = bitvec::vec::BitVec::from_bitslice(
-            &kmer_without_minimizer[..(minimizer_offset as usize * 2)],
-        );
+        let minimizer_offset = read_nb_kmer(inner, max_offset(k, max))?;
+        if minimizer_offset > length as u64 {
+            return Err(
+                error::Kff::MinimizerOffsetTooLarge(minimizer_offset, length as u64).into(),
+            );
+        }
+        let minimizer_offset = minimizer_offset as usize;
+
+        let kmer_without_minimizer = inner.read_2bits(length)?;
+
+        let mut kmer =
+            bitvec::vec::BitVec::from_bitslice(&kmer_without_minimizer[..(minimizer_offset * 2)]);
         kmer.extend_from_bitslice(minimizer);
-        kmer.extend_from_bitslice(&kmer_without_minimizer[(minimizer_offset as usize * 2)..]);
+        kmer.extend_from_bitslice(&kmer_without_minimizer[(minimizer_offset * 2)..]);
 
-        let data = inner.read_n_bytes_dyn((nb_kmer * data_size) as usize)?;
+        let data = inner.read_n_bytes_dyn(data_length)?;
 
         Ok(Self {
             k,
@@ -125,23 +290,64 @@ impl Block {
         })
     }
 
+    /// Skip minimizer block, return number of kmer in block
+    pub fn skip_minimizer<R>(
+        inner: &mut R,
+        k: u64,
+        m: u64,
+        data_size: usize,
+        max: u64,
+    ) -> error::Result<u64>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        Self::skip_minimizer_with_limits(inner, k, m, data_size, max, &crate::ReadLimits::default())
+            .map(|(nb_kmer, _)| nb_kmer)
+    }
+
+    /// Skip minimizer block, size of block is check against `limits`, return number of kmer and size in bytes of block
+    pub fn skip_minimizer_with_limits<R>(
+        inner: &mut R,
+        k: u64,
+        m: u64,
+        data_size: usize,
+        max: u64,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<(u64, u64)>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        check_parameters(k, m, max)?;
+
+        let nb_kmer = read_block_nb_kmer(inner, max)?;
+        let length = nb_nucleotide(nb_kmer, k, m)?;
+        let data_length = data_length(nb_kmer, data_size)?;
+        check_block_length(length, data_length, limits)?;
+
+        read_nb_kmer(inner, max_offset(k, max))?;
+
+        let size = (crate::bytes2store_k(length as u64) as usize)
+            .checked_add(data_length)
+            .ok_or(error::Kff::ValueOverflow(nb_kmer, data_size as u64))?;
+        inner.skip_n_bytes(size)?;
+
+        Ok((
+            nb_kmer,
+            block_nb_kmer_size(max) + nb_kmer_size(max_offset(k, max)) + size as u64,
+        ))
+    }
+
     /// Write minimizer block
     pub fn write_minimizer<W>(&self, outer: &mut W, m: usize, max: u64) -> error::Result<()>
     where
         W: std::io::Write + crate::KffWrite,
     {
-        if max > 1 {
-            write_nb_kmer(
-                outer,
-                max,
-                (self.kmer.seq2bit().len() / 2 - self.k as usize + 1) as u64,
-            )?;
+        if (self.minimizer_offset + m) * 2 > self.kmer.seq2bit().len() {
+            return Err(error::Kff::MinimizerNotFound(self.minimizer_offset as u64).into());
         }
-        write_nb_kmer(
-            outer,
-            std::cmp::min(self.k + max - 1, u64::MAX),
-            self.minimizer_offset as u64,
-        )?;
+
+        write_block_nb_kmer(outer, max, self.nb_kmer())?;
+        write_nb_kmer(outer, max_offset(self.k, max), self.minimizer_offset as u64)?;
 
         let mut kmer =
             bitvec::vec::BitVec::from_bitslice(&self.kmer.seq2bit()[..(self.minimizer_offset * 2)]);
@@ -162,9 +368,13 @@ impl Block {
             let k_range = self.offset * 2..(self.offset + self.k as usize) * 2;
             let d_range = self.offset * self.data_size..(self.offset + 1) * self.data_size;
 
+            // Copy in an empty vector to get a kmer aligned on first bit of first byte
+            let mut seq = bitvec::vec::BitVec::with_capacity(self.k as usize * 2);
+            seq.extend_from_bitslice(&self.kmer.seq2bit()[k_range]);
+
             self.offset += 1;
             Some(Kmer::new(
-                bitvec::boxed::BitBox::from_bitslice(&self.kmer.seq2bit()[k_range]),
+                seq.into_boxed_bitslice(),
                 self.kmer.data()[d_range].to_vec(),
             ))
         }
@@ -179,6 +389,107 @@ impl std::iter::Iterator for Block {
     }
 }
 
+pub(crate) fn nb_kmer_size(max: u64) -> u64 {
+    match max.leading_zeros() {
+        0..=31 => 8,
+        32..=47 => 4,
+        48..=55 => 2,
+        56..=64 => 1,
+        _ => unreachable!("You can't have more than 64 leading_zeros() with an u64"),
+    }
+}
+
+/// Check `k`, `m` and `max` of a section can describe a block
+pub(crate) fn check_parameters(k: u64, m: u64, max: u64) -> error::Result<()> {
+    if k == 0 {
+        Err(error::Kff::KmerSizeIsZero.into())
+    } else if m > k {
+        Err(error::Kff::MinimizerSizeTooLarge(m, k).into())
+    } else if max == 0 {
+        Err(error::Kff::MaxIsZero.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Value used to size minimizer offset field, `k + max - 1` saturate to [u64::MAX]
+pub(crate) fn max_offset(k: u64, max: u64) -> u64 {
+    k.saturating_add(max.saturating_sub(1))
+}
+
+/// Number of nucleotide of a block with `nb_kmer` kmer of size `k` without `m` nucleotides of minimizer
+fn nb_nucleotide(nb_kmer: u64, k: u64, m: u64) -> error::Result<usize> {
+    (nb_kmer - 1)
+        .checked_add(k - m)
+        .and_then(|x| usize::try_from(x).ok())
+        .filter(|x| *x <= bitvec::slice::BitSlice::<u8, bitvec::order::Msb0>::MAX_BITS / 2)
+        .ok_or_else(|| error::Kff::ValueOverflow(nb_kmer, k).into())
+}
+
+/// Number of bytes of data of a block with `nb_kmer` kmer
+fn data_length(nb_kmer: u64, data_size: usize) -> error::Result<usize> {
+    usize::try_from(nb_kmer)
+        .ok()
+        .and_then(|x| x.checked_mul(data_size))
+        .ok_or_else(|| error::Kff::ValueOverflow(nb_kmer, data_size as u64).into())
+}
+
+/// Check size in bytes of `nb_nucleotide` nucleotides and `data_length` bytes of data against `limits`
+fn check_block_length(
+    nb_nucleotide: usize,
+    data_length: usize,
+    limits: &crate::ReadLimits,
+) -> error::Result<()> {
+    crate::limits::check(
+        "block length",
+        crate::bytes2store_k(nb_nucleotide as u64).saturating_add(data_length as u64),
+        *limits.max_block_length(),
+    )
+}
+
+/// Number of bytes of number of kmer field of a block, field is absent if `max` is 1
+fn block_nb_kmer_size(max: u64) -> u64 {
+    if max > 1 {
+        nb_kmer_size(max)
+    } else {
+        0
+    }
+}
+
+/// Read number of kmer in a block, field is absent if `max` is 1
+pub(crate) fn read_block_nb_kmer<R>(inner: &mut R, max: u64) -> error::Result<u64>
+where
+    R: std::io::Read + crate::KffRead,
+{
+    let nb_kmer = match max {
+        0 => return Err(error::Kff::MaxIsZero.into()),
+        1 => 1,
+        _ => read_nb_kmer(inner, max)?,
+    };
+
+    if nb_kmer == 0 || nb_kmer > max {
+        Err(error::Kff::InvalidNbKmer(nb_kmer, max).into())
+    } else {
+        Ok(nb_kmer)
+    }
+}
+
+/// Write number of kmer in a block, field is absent if `max` is 1
+pub(crate) fn write_block_nb_kmer<W>(outer: &mut W, max: u64, nb_kmer: u64) -> error::Result<()>
+where
+    W: std::io::Write + crate::KffWrite,
+{
+    if max == 0 {
+        Err(error::Kff::MaxIsZero.into())
+    } else if nb_kmer == 0 || nb_kmer > max {
+        Err(error::Kff::InvalidNbKmer(nb_kmer, max).into())
+    } else if max == 1 {
+        Ok(())
+    } else {
+        write_nb_kmer(outer, max, nb_kmer)
+    }
+}
+
 pub(crate) fn read_nb_kmer<R>(inner: &mut R, max: u64) -> error::Result<u64>
 where
     R: std::io::Read + crate::KffRead,
@@ -196,6 +507,10 @@ pub(crate) fn write_nb_kmer<W>(outer: &mut W, max: u64, value: u64) -> error::Re
 where
     W: std::io::Write + crate::KffWrite,
 {
+    if nb_kmer_size(value) > nb_kmer_size(max) {
+        return Err(error::Kff::ValueOverflow(value, max).into());
+    }
+
     match max.leading_zeros() {
         0..=31 => Ok(outer.write_u64(&value)?),
         32..=47 => Ok(outer.write_u32(&(value as u32))?),
@@ -237,6 +552,9 @@ mod tests {
             );
             assert_eq!(&datas[..], &[vec![1], vec![2], vec![3],]);
 
+            // Kmer are aligned on first bit
+            assert_eq!(kmers[1].as_raw_slice(), &[0b01101111, 0b11000000]);
+
             Ok(())
         }
 
@@ -261,7 +579,7 @@ mod tests {
                     bitvec::bitbox![u8, bitvec::order::Msb0; 1, 0, 1, 1, 1, 1, 1, 1, 0, 1],
                 ]
             );
-            assert_eq!(&datas[..], &[vec![], vec![], vec![],]);
+            assert_eq!(&datas[..], &[Vec::<u8>::new(), Vec::new(), Vec::new()]);
 
             Ok(())
         }
@@ -288,6 +606,16 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn skip() -> error::Result<()> {
+            let mut readable: &[u8] = &[3, 0b00011011, 0b11110100, 1, 2, 3, 42];
+
+            assert_eq!(Block::skip_raw(&mut readable, 5, 1, 255)?, 3);
+            assert_eq!(readable, &[42]);
+
+            Ok(())
+        }
+
         #[test]
         fn write() -> error::Result<()> {
             let block = Block {
@@ -363,7 +691,7 @@ mod tests {
                     bitvec::bitbox![u8, bitvec::order::Msb0; 1, 0, 1, 1, 1, 1, 1, 1, 0, 1],
                 ]
             );
-            assert_eq!(&datas[..], &[vec![], vec![], vec![],]);
+            assert_eq!(&datas[..], &[Vec::<u8>::new(), Vec::new(), Vec::new()]);
 
             Ok(())
         }
@@ -391,6 +719,16 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn skip() -> error::Result<()> {
+            let mut readable: &[u8] = &[3, 1, 0b00111101, 1, 2, 3, 42];
+
+            assert_eq!(Block::skip_minimizer(&mut readable, 5, 3, 1, 200)?, 3);
+            assert_eq!(readable, &[42]);
+
+            Ok(())
+        }
+
         #[test]
         fn write() -> error::Result<()> {
             let block = Block {
@@ -482,4 +820,277 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn value_overflow() -> error::Result<()> {
+        let mut writable = Vec::new();
+
+        write_nb_kmer(&mut writable, 200, u8::MAX as u64)?;
+        assert_eq!(writable, vec![255]);
+
+        assert!(matches!(
+            write_nb_kmer(&mut writable, 200, u8::MAX as u64 + 1),
+            Err(error::Error::Kff(error::Kff::ValueOverflow(256, 200)))
+        ));
+        assert!(matches!(
+            write_nb_kmer(&mut writable, u16::MAX as u64, u32::MAX as u64),
+            Err(error::Error::Kff(error::Kff::ValueOverflow(_, _)))
+        ));
+        assert_eq!(writable, vec![255]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn new_with_minimizer() -> error::Result<()> {
+        let encoding = 0b00011011;
+        let minimizer = |seq: &[u8]| crate::kmer::seq2bits(seq, encoding);
+
+        // CT is in ACTGA and CTGAC, AC only in ACTGA
+        let kmer = Kmer::from_ascii(b"ACTGAC", vec![], encoding);
+        let block = Block::new_with_minimizer(5, 0, kmer.clone(), &minimizer(b"CT"))?;
+        assert_eq!(block.minimizer_offset(), &1);
+        block.validate(5, 0, 2, Some(&minimizer(b"CT")))?;
+
+        assert!(matches!(
+            Block::new_with_minimizer(5, 0, kmer.clone(), &minimizer(b"AC")),
+            Err(error::Error::Kff(error::Kff::MinimizerNotInBlock))
+        ));
+        assert!(matches!(
+            Block::new_with_minimizer(5, 0, kmer, &minimizer(b"GG")),
+            Err(error::Error::Kff(error::Kff::MinimizerNotInBlock))
+        ));
+
+        // First shared occurrence is choose
+        let kmer = Kmer::from_ascii(b"ACACA", vec![], encoding);
+        let block = Block::new_with_minimizer(5, 0, kmer, &minimizer(b"CA"))?;
+        assert_eq!(block.minimizer_offset(), &1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> error::Result<()> {
+        let block = Block::new(5, 1, Kmer::from_ascii(b"ACTGAC", vec![1, 2], 0b00011011), 1);
+
+        block.validate(5, 1, 2, None)?;
+        block.validate(5, 1, 2, Some(&crate::kmer::seq2bits(b"CTG", 0b00011011)))?;
+
+        assert!(matches!(
+            block.validate(4, 1, 2, None),
+            Err(error::Error::Kff(error::Kff::KmerSizeMismatch(4, 5)))
+        ));
+        assert!(matches!(
+            block.validate(5, 1, 1, None),
+            Err(error::Error::Kff(error::Kff::BlockTooLong(2, 1)))
+        ));
+        assert!(matches!(
+            block.validate(5, 2, 2, None),
+            Err(error::Error::Kff(error::Kff::BadDataLength(4, 2)))
+        ));
+        assert!(matches!(
+            block.validate(5, 1, 2, Some(&crate::kmer::seq2bits(b"AC", 0b00011011))),
+            Err(error::Error::Kff(error::Kff::MinimizerNotFound(1)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn edge_max() -> error::Result<()> {
+        let mut readable: &[u8] = &[0b00011011, 0b11000000, 1];
+        assert!(matches!(
+            Block::read_raw(&mut readable, 5, 1, 0),
+            Err(error::Error::Kff(error::Kff::MaxIsZero))
+        ));
+        assert!(matches!(
+            Block::read_raw(&mut readable, 0, 1, 1),
+            Err(error::Error::Kff(error::Kff::KmerSizeIsZero))
+        ));
+
+        // No number of kmer field if max is 1
+        let block = Block::new(5, 1, Kmer::from_ascii(b"ACTGA", vec![1], 0b00011011), 0);
+        let mut writable = Vec::new();
+        block.write_raw(&mut writable, 1)?;
+        assert_eq!(writable, vec![0b00011011, 0b00000000, 1]);
+
+        assert!(matches!(
+            block.write_raw(&mut writable, 0),
+            Err(error::Error::Kff(error::Kff::MaxIsZero))
+        ));
+
+        let block = Block::new(5, 1, Kmer::from_ascii(b"ACTGAC", vec![1, 2], 0b00011011), 0);
+        assert!(matches!(
+            block.write_raw(&mut writable, 1),
+            Err(error::Error::Kff(error::Kff::InvalidNbKmer(2, 1)))
+        ));
+
+        // Number of kmer must be between 1 and max
+        for nb_kmer in [0, 4] {
+            let mut readable: &[u8] = &[nb_kmer, 0b00011011, 0b11000000, 1];
+            assert!(matches!(
+                Block::read_raw(&mut readable, 5, 1, 3),
+                Err(error::Error::Kff(error::Kff::InvalidNbKmer(n, 3))) if n == nb_kmer as u64
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn edge_minimizer_offset() -> error::Result<()> {
+        // k + max - 1 saturate, offset is store on 8 bytes
+        assert_eq!(max_offset(u64::MAX, 2), u64::MAX);
+        assert_eq!(max_offset(1, u64::MAX), u64::MAX);
+        assert_eq!(max_offset(5, 1), 5);
+
+        let mut readable: &[u8] = &[
+            0, 0, 0, 0, 0, 0, 0, 1, // number of kmer
+            0, 0, 0, 0, 0, 0, 0, 1,          // minimizer offset
+            0b00110000, // kmer without minimizer
+        ];
+        let minimizer = bitvec::bitbox![u8, bitvec::order::Msb0; 0, 1, 1, 0, 1, 1];
+        let block = Block::read_minimizer(&mut readable, 5, 3, 0, u64::MAX - 2, &minimizer)?;
+        assert_eq!(
+            block.kmer().seq2bit(),
+            &crate::kmer::seq2bits(b"ACTGG", 0b00011011)
+        );
+        assert_eq!(block.minimizer_offset(), &1);
+        assert!(readable.is_empty());
+
+        // Offset after end of block, no number of kmer field if max is 1
+        let mut readable: &[u8] = &[3, 0b00110000];
+        assert!(matches!(
+            Block::read_minimizer(&mut readable, 5, 3, 0, 1, &minimizer),
+            Err(error::Error::Kff(error::Kff::MinimizerOffsetTooLarge(3, 2)))
+        ));
+
+        let mut readable: &[u8] = &[1, 3, 0b00111101];
+        assert!(matches!(
+            Block::read_minimizer(&mut readable, 2, 3, 0, 1, &minimizer),
+            Err(error::Error::Kff(error::Kff::MinimizerSizeTooLarge(3, 2)))
+        ));
+
+        // Number of nucleotide can't be represent
+        let mut readable: &[u8] = &[255, 255, 255, 255, 255, 255, 255, 255];
+        assert!(matches!(
+            Block::read_raw(&mut readable, u64::MAX, 0, u64::MAX),
+            Err(error::Error::Kff(error::Kff::ValueOverflow(_, _)))
+        ));
+
+        Ok(())
+    }
+
+    mod property {
+        use super::*;
+
+        use proptest::prelude::*;
+
+        /// Max of each size of number of kmer field and bounds
+        fn max() -> impl Strategy<Value = u64> {
+            prop_oneof![
+                Just(1),
+                Just(u8::MAX as u64),
+                Just(u8::MAX as u64 + 1),
+                Just(u16::MAX as u64),
+                Just(u16::MAX as u64 + 1),
+                Just(u32::MAX as u64),
+                Just(u32::MAX as u64 + 1),
+                Just(u64::MAX),
+                1..u64::MAX,
+            ]
+        }
+
+        /// Kmer size, number of kmer and sequence of a block
+        fn block() -> impl Strategy<Value = (usize, usize, Vec<u8>)> {
+            (1usize..40, 1usize..20).prop_flat_map(|(k, nb_kmer)| {
+                (
+                    Just(k),
+                    Just(nb_kmer),
+                    proptest::collection::vec(
+                        proptest::sample::select(&b"ACTG"[..]),
+                        k + nb_kmer - 1,
+                    ),
+                )
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn nb_kmer_round_trip(max in max(), value in any::<u64>()) {
+                let value = value % max.saturating_add(1).max(1);
+
+                let mut writable = Vec::new();
+                write_nb_kmer(&mut writable, max, value)?;
+                prop_assert_eq!(writable.len() as u64, nb_kmer_size(max));
+
+                let mut readable: &[u8] = &writable;
+                prop_assert_eq!(read_nb_kmer(&mut readable, max)?, value);
+                prop_assert!(readable.is_empty());
+            }
+
+            #[test]
+            fn nb_kmer_overflow(max in max(), value in any::<u64>()) {
+                let result = write_nb_kmer(&mut Vec::new(), max, value);
+
+                prop_assert_eq!(result.is_ok(), nb_kmer_size(value) <= nb_kmer_size(max));
+            }
+
+            #[test]
+            fn raw_round_trip(
+                (k, nb_kmer, seq) in block(),
+                max in max(),
+                data_size in 0usize..3,
+                seed in any::<u8>(),
+            ) {
+                let max = max.max(nb_kmer as u64);
+                let data: Vec<u8> = (0..nb_kmer * data_size).map(|x| (x as u8).wrapping_add(seed)).collect();
+
+                let block = Block::new(k as u64, data_size, Kmer::from_ascii(&seq, data, 0b00011011), 0);
+                block.validate(k as u64, data_size as u64, max, None)?;
+
+                let mut writable = Vec::new();
+                block.write_raw(&mut writable, max)?;
+                prop_assert_eq!(writable.len() as u64, block.raw_size(max));
+
+                let mut readable: &[u8] = &writable;
+                prop_assert_eq!(&Block::read_raw(&mut readable, k as u64, data_size, max)?, &block);
+
+                let mut readable: &[u8] = &writable;
+                prop_assert_eq!(Block::skip_raw(&mut readable, k as u64, data_size, max)?, nb_kmer as u64);
+                prop_assert!(readable.is_empty());
+            }
+
+            #[test]
+            fn minimizer_round_trip(
+                (k, nb_kmer, seq) in block(),
+                m in any::<usize>(),
+                max in max(),
+                offset in any::<usize>(),
+            ) {
+                let m = m % (k + 1);
+                let max = max.max(nb_kmer as u64);
+
+                // Minimizer is in all kmers
+                let offset = nb_kmer - 1 + offset % (k - m + 1);
+                let minimizer = crate::kmer::seq2bits(&seq[offset..offset + m], 0b00011011);
+
+                let block = Block::new(k as u64, 0, Kmer::from_ascii(&seq, vec![], 0b00011011), offset);
+                block.validate(k as u64, 0, max, Some(&minimizer))?;
+
+                let mut writable = Vec::new();
+                block.write_minimizer(&mut writable, m, max)?;
+                prop_assert_eq!(writable.len() as u64, block.minimizer_size(m as u64, max));
+
+                let mut readable: &[u8] = &writable;
+                let read = Block::read_minimizer(&mut readable, k as u64, m as u64, 0, max, &minimizer)?;
+                prop_assert_eq!(read.kmer(), block.kmer());
+                prop_assert_eq!(read.minimizer_offset(), &offset);
+
+                let mut readable: &[u8] = &writable;
+                prop_assert_eq!(Block::skip_minimizer(&mut readable, k as u64, m as u64, 0, max)?, nb_kmer as u64);
+                prop_assert!(readable.is_empty());
+            }
+        }
+    }
 }
@@ -8,7 +8,9 @@
 use crate::error;
 
 /// Struct to parse, manage and write Header information
-#[derive(std::fmt::Debug, getset::Getters, getset::Setters, getset::MutGetters)]
+#[derive(
+    std::clone::Clone, std::fmt::Debug, getset::Getters, getset::Setters, getset::MutGetters,
+)]
 #[getset(get = "pub")]
 pub struct Header {
     /// Major version number
@@ -59,6 +61,14 @@ impl Header {
 
     /// Read a readable to create a new header
     pub fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        Self::read_with_limits(inner, &crate::ReadLimits::default())
+    }
+
+    /// Read a readable to create a new header, free block size is check against `limits`
+    pub fn read_with_limits<R>(inner: &mut R, limits: &crate::ReadLimits) -> error::Result<Self>
     where
         R: std::io::Read + crate::KffRead,
     {
@@ -82,9 +92,14 @@ impl Header {
         obj.uniq_kmer = inner.read_bool()?;
         obj.canonical_kmer = inner.read_bool()?;
 
-        let free_block_size = inner.read_u32()? as usize;
+        let free_block_size = inner.read_u32()?;
+        crate::limits::check(
+            "free block size",
+            free_block_size as u64,
+            *limits.max_free_block(),
+        )?;
 
-        obj.free_block = inner.read_n_bytes_dyn(free_block_size)?;
+        obj.free_block = inner.read_n_bytes_dyn(free_block_size as usize)?;
 
         obj.check()?;
 
@@ -104,7 +119,7 @@ impl Header {
         outer.write_bool(&self.canonical_kmer)?; // Canonical kmer
         outer.write_u32(&(self.free_block.len() as u32))?; // Size of free block
         if !self.free_block.is_empty() {
-            outer.write_ascii(&self.free_block)?; // Free block
+            outer.write_bytes(&self.free_block)?; // Free block
         }
 
         Ok(())
@@ -137,6 +152,18 @@ impl Header {
         Ok(self)
     }
 
+    /// Parse free block as key-value metadata, see [Metadata](super::Metadata) for format
+    pub fn metadata(&self) -> error::Result<super::Metadata> {
+        super::Metadata::parse(&self.free_block)
+    }
+
+    /// Replace free block by serialized metadata
+    pub fn set_metadata(&mut self, metadata: &super::Metadata) -> &mut Self {
+        self.free_block = metadata.to_bytes();
+
+        self
+    }
+
     /// Function run after construction of header to check value
     fn check(&self) -> error::Result<&Self> {
         self.check_version()?.check_encoding()
@@ -175,7 +202,7 @@ mod tests {
     use super::*;
 
     const VALID: &[u8] = &[
-        b'K', b'F', b'F', 1, 0, 0b00101110, 1, 0, 0, 0, 0, 4, b't', b'e', b's', b't', 0,
+        b'K', b'F', b'F', 1, 0, 0b00101110, 1, 0, 0, 0, 0, 4, b't', b'e', b's', b't',
     ];
 
     const BAD_MAGIC_NUMBER: &[u8] = b"KKF";
@@ -239,4 +266,34 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn metadata() -> error::Result<()> {
+        let mut header = Header::new(1, 0, 0b00101110, true, false, b"producer: kmc".to_vec())?;
+
+        let mut metadata = header.metadata()?;
+        assert_eq!(
+            metadata.get(crate::section::metadata::PRODUCER),
+            Some("kmc")
+        );
+
+        metadata.insert("samples", "a,b")?;
+        header.set_metadata(&metadata);
+
+        let mut writer = Vec::new();
+        header.write(&mut writer)?;
+        let header = Header::read(&mut std::io::Cursor::new(writer))?;
+        assert_eq!(header.free_block(), b"producer: kmc\nsamples: a,b");
+        assert_eq!(header.metadata()?, metadata);
+
+        // Arbitrary bytes still round trip
+        let header = Header::new(1, 0, 0b00101110, true, false, vec![0, 255, b'\n'])?;
+        let mut writer = Vec::new();
+        header.write(&mut writer)?;
+        let header = Header::read(&mut std::io::Cursor::new(writer))?;
+        assert_eq!(header.free_block(), &[0, 255, b'\n']);
+        assert!(header.metadata().is_err());
+
+        Ok(())
+    }
 }
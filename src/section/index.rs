@@ -27,7 +27,13 @@ impl Index {
     {
         let nb_block = inner.read_u64()?;
 
-        let _ = inner.read_n_bytes_dyn((8 * nb_block + nb_block + 8) as usize)?;
+        // Each pair is a section type and a delta, followed by next index
+        let size = nb_block
+            .checked_mul(9)
+            .and_then(|x| x.checked_add(8))
+            .and_then(|x| usize::try_from(x).ok())
+            .ok_or(error::Kff::ValueOverflow(nb_block, 9))?;
+        inner.skip_n_bytes(size)?;
 
         Ok(Self {
             pair: Vec::default(),
@@ -44,12 +50,25 @@ impl Index {
 
     /// Read an Index section, section flag must be already read
     pub fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        Self::read_with_limits(inner, &crate::ReadLimits::default())
+    }
+
+    /// Same as [Index::read] but size of index is check against `max_section_size` of `limits`
+    pub fn read_with_limits<R>(inner: &mut R, limits: &crate::ReadLimits) -> error::Result<Self>
     where
         R: std::io::Read + crate::KffRead,
     {
         let mut pair = Vec::new();
 
         let nb_block = inner.read_u64()?;
+        crate::limits::check(
+            "index size",
+            nb_block.saturating_mul(9),
+            *limits.max_section_size(),
+        )?;
 
         for _ in 0..nb_block {
             let section_type = inner.read_u8()?;
@@ -149,6 +168,13 @@ mod tests {
         assert_eq!(index.pair(), &[]);
         assert_eq!(index.next_index(), &0);
 
+        // Size of section overflow
+        let mut data: &[u8] = &[255, 255, 255, 255, 255, 255, 255, 255];
+        assert!(matches!(
+            Index::skip(&mut data),
+            Err(error::Error::Kff(error::Kff::ValueOverflow(_, 9)))
+        ));
+
         Ok(())
     }
 }
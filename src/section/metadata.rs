@@ -0,0 +1,196 @@
+//! Key-value metadata stored in header free block
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+
+/// Name of program that write file
+pub const PRODUCER: &str = "producer";
+
+/// Version of program that write file
+pub const VERSION: &str = "version";
+
+/// Command line used to write file
+pub const COMMAND: &str = "command";
+
+/// Names of samples, separate by comma
+pub const SAMPLES: &str = "samples";
+
+/// Date of file creation
+pub const DATE: &str = "date";
+
+/// Ordered key-value metadata of a header free block
+///
+/// Free block is UTF-8 text, one `key: value` entry per line, lines are separate by `\n`. Key
+/// can't be empty or contains `:` or a line break, value can't contains a line break, spaces
+/// around key and value are ignored and empty lines are skipped. A free block write before this
+/// convention, like `producer: kmc`, already follow it. Arbitrary bytes are still accepted by
+/// [Header](super::Header) but can't be parsed.
+///
+/// ```
+/// let mut metadata = kff::section::Metadata::new();
+/// metadata.insert("producer", "kff from-reads")?;
+/// metadata.insert("samples", "a,b")?;
+/// assert_eq!(metadata.to_bytes(), b"producer: kff from-reads\nsamples: a,b");
+///
+/// let metadata = kff::section::Metadata::parse(b"producer: kmc\n")?;
+/// assert_eq!(metadata.get("producer"), Some("kmc"));
+/// # Ok::<(), kff::error::Error>(())
+/// ```
+#[derive(
+    std::clone::Clone, std::fmt::Debug, std::default::Default, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub struct Metadata {
+    entries: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// Create an empty Metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a free block, error if it doesn't follow metadata convention
+    pub fn parse(free_block: &[u8]) -> error::Result<Self> {
+        let text = std::str::from_utf8(free_block).map_err(|_| error::Kff::NotMetadata)?;
+
+        let mut metadata = Self::new();
+        for line in text.split('\n') {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(':').ok_or(error::Kff::NotMetadata)?;
+            metadata
+                .insert(key, value)
+                .map_err(|_| error::Kff::NotMetadata)?;
+        }
+
+        Ok(metadata)
+    }
+
+    /// Serialize metadata in free block format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<String>>()
+            .join("\n")
+            .into_bytes()
+    }
+
+    /// Get value associate to key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key.trim())
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Set value of key, replace previous value at same place or add it at end
+    pub fn insert(&mut self, key: &str, value: &str) -> error::Result<&mut Self> {
+        let (key, value) = (key.trim(), value.trim());
+
+        if key.is_empty() || key.contains([':', '\n', '\r']) {
+            return Err(error::Kff::InvalidMetadata(key.to_string()).into());
+        }
+        if value.contains(['\n', '\r']) {
+            return Err(error::Kff::InvalidMetadata(value.to_string()).into());
+        }
+
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => self.entries.push((key.to_string(), value.to_string())),
+        }
+
+        Ok(self)
+    }
+
+    /// Remove key, return its value
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let position = self.entries.iter().position(|(k, _)| k == key.trim())?;
+
+        Some(self.entries.remove(position).1)
+    }
+
+    /// Iterate over key and value in order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if there isn't any entry
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> error::Result<()> {
+        let metadata = Metadata::parse(b"producer: kmc\r\n\n version:1.0 \ncommand: kff a:b\n")?;
+        assert_eq!(
+            metadata.iter().collect::<Vec<_>>(),
+            vec![
+                ("producer", "kmc"),
+                ("version", "1.0"),
+                ("command", "kff a:b")
+            ]
+        );
+
+        assert!(Metadata::parse(b"")?.is_empty());
+        assert!(matches!(
+            Metadata::parse(b"test"),
+            Err(error::Error::Kff(error::Kff::NotMetadata))
+        ));
+        assert!(matches!(
+            Metadata::parse(b": empty key"),
+            Err(error::Error::Kff(error::Kff::NotMetadata))
+        ));
+        assert!(matches!(
+            Metadata::parse(&[b'a', b':', 0xFF]),
+            Err(error::Error::Kff(error::Kff::NotMetadata))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn edit() -> error::Result<()> {
+        let mut metadata = Metadata::parse(b"producer: kmc\nsamples: a")?;
+
+        metadata
+            .insert(PRODUCER, "kff")?
+            .insert(DATE, "2024-01-01")?;
+        assert_eq!(metadata.get("producer"), Some("kff"));
+        assert_eq!(metadata.remove(SAMPLES), Some("a".to_string()));
+        assert_eq!(metadata.remove(SAMPLES), None);
+        assert_eq!(metadata.len(), 2);
+
+        assert_eq!(metadata.to_bytes(), b"producer: kff\ndate: 2024-01-01");
+        assert_eq!(Metadata::parse(&metadata.to_bytes())?, metadata);
+
+        assert!(matches!(
+            metadata.insert("a:b", "c"),
+            Err(error::Error::Kff(error::Kff::InvalidMetadata(_)))
+        ));
+        assert!(matches!(
+            metadata.insert(COMMAND, "a\nb"),
+            Err(error::Error::Kff(error::Kff::InvalidMetadata(_)))
+        ));
+        assert!(metadata.insert(" ", "a").is_err());
+
+        Ok(())
+    }
+}
@@ -27,6 +27,9 @@ pub struct Minimizer {
 
     /// Size in bytes of data associate to each kmer
     data_size: u64,
+
+    /// Limits check when blocks are read
+    limits: crate::ReadLimits,
 }
 
 impl Minimizer {
@@ -55,6 +58,7 @@ impl Minimizer {
                 .get("data_size")
                 .cloned()
                 .ok_or_else(|| error::Kff::FieldIsMissing("data_size".to_string()))?,
+            limits: crate::ReadLimits::default(),
         })
     }
 
@@ -63,35 +67,150 @@ impl Minimizer {
     where
         R: std::io::Read + crate::KffRead,
     {
-        let mut output = Vec::new();
+        let (_, blocks) = self.read_blocks(inner)?;
+
+        Ok(blocks.into_iter().flatten().collect())
+    }
 
-        let minimizer = inner.read_2bits(self.m as usize)?.into_boxed_bitslice();
+    /// Read minimizer and blocks of a Minimizer section, section flag must be already read
+    ///
+    /// Size of each block and of all blocks are check against `limits`.
+    pub fn read_blocks<R>(&self, inner: &mut R) -> error::Result<(Seq2Bit, Vec<section::Block>)>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        let minimizer = self.read_minimizer(inner)?;
 
         let nb_block = inner.read_u64()?;
 
+        let mut output = Vec::new();
+        let mut size = 0u64;
         for _ in 0..nb_block {
-            let block = section::Block::read_minimizer(
+            let block = section::Block::read_minimizer_with_limits(
                 inner,
                 self.k,
                 self.m,
                 self.data_size as usize,
                 self.max,
                 &minimizer,
+                &self.limits,
             )?;
 
-            output.extend(block);
+            size = size.saturating_add(block.minimizer_size(self.m, self.max));
+            crate::limits::check("section size", size, *self.limits.max_section_size())?;
+
+            output.push(block);
         }
 
-        Ok(output)
+        Ok((minimizer, output))
     }
 
-    /// Write a Raw section, section flag isn't read
+    /// Skip a Minimizer section, section flag must be already read, return number of kmer in section
+    ///
+    /// Size of each block and of all blocks are check against `limits`.
+    pub fn skip<R>(&self, inner: &mut R) -> error::Result<u64>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        let length = self.minimizer_length()?;
+        inner.skip_n_bytes(crate::bytes2store_k(length as u64) as usize)?;
+
+        let nb_block = inner.read_u64()?;
+
+        let mut nb_kmer = 0;
+        let mut size = 0u64;
+        for _ in 0..nb_block {
+            let (block_kmer, block_size) = section::Block::skip_minimizer_with_limits(
+                inner,
+                self.k,
+                self.m,
+                self.data_size as usize,
+                self.max,
+                &self.limits,
+            )?;
+            nb_kmer += block_kmer;
+
+            size = size.saturating_add(block_size);
+            crate::limits::check("section size", size, *self.limits.max_section_size())?;
+        }
+
+        Ok(nb_kmer)
+    }
+
+    /// Read only minimizer of a Minimizer section, section flag must be already read
+    pub(crate) fn read_minimizer<R>(&self, inner: &mut R) -> error::Result<Seq2Bit>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        Ok(inner
+            .read_2bits(self.minimizer_length()?)?
+            .into_boxed_bitslice())
+    }
+
+    /// Number of nucleotides of minimizer, `k`, `m` and `max` are checked before minimizer is read
+    fn minimizer_length(&self) -> error::Result<usize> {
+        section::block::check_parameters(self.k, self.m, self.max)?;
+
+        let max_length = bitvec::slice::BitSlice::<u8, bitvec::order::Msb0>::MAX_BITS / 2;
+        let length = usize::try_from(self.m)
+            .ok()
+            .filter(|length| *length <= max_length)
+            .ok_or_else(|| {
+                error::Kff::LimitExceeded("minimizer size".to_string(), self.m, max_length as u64)
+            })?;
+
+        crate::limits::check(
+            "minimizer length",
+            crate::bytes2store_k(length as u64),
+            *self.limits.max_block_length(),
+        )?;
+
+        Ok(length)
+    }
+
+    /// Check minimizer has size `m` and all blocks can be write in this section, see [Block::validate](section::Block::validate)
+    pub fn validate(
+        &self,
+        minimizer: &Seq2Bit,
+        blocks: &[section::block::Block],
+    ) -> error::Result<()> {
+        if minimizer.len() as u64 != self.m * 2 {
+            return Err(
+                error::Kff::MinimizerSizeMismatch(self.m, minimizer.len() as u64 / 2).into(),
+            );
+        }
+
+        for block in blocks {
+            block.validate(self.k, self.data_size, self.max, Some(minimizer))?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a Minimizer section, section flag isn't write
+    ///
+    /// Minimizer and blocks are checked with [Minimizer::validate] before any write.
     pub fn write<W>(
         &self,
         outer: &mut W,
         minimizer: Seq2Bit,
         blocks: &[section::block::Block],
     ) -> error::Result<()>
+    where
+        W: std::io::Write + crate::KffWrite,
+    {
+        self.validate(&minimizer, blocks)?;
+
+        self.write_unchecked(outer, minimizer, blocks)
+    }
+
+    /// Write a Minimizer section without check minimizer and blocks, they must be validated before
+    pub(crate) fn write_unchecked<W>(
+        &self,
+        outer: &mut W,
+        minimizer: Seq2Bit,
+        blocks: &[section::block::Block],
+    ) -> error::Result<()>
     where
         W: std::io::Write + crate::KffWrite,
     {
@@ -190,6 +309,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn skip() -> error::Result<()> {
+        let mut values = section::Values::with_capacity(5);
+
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 3);
+        values.insert("ordered".to_string(), false as u64);
+        values.insert("max".to_string(), 100);
+        values.insert("data_size".to_string(), 1);
+
+        let minimizer = Minimizer::new(&values)?;
+
+        let mut data: &[u8] = &[
+            0b01101100, // minimizer sequence
+            0, 0, 0, 0, 0, 0, 0, 3, // number of block
+            3, 1, 0b00111101, 1, 2, 3, // one block with 3 kmer and 1 bytes data
+            2, 1, 0b00111111, 1, 2, // one block with 2 kmer and 1 bytes data
+            1, 1, 0b00110000, 1,    // one block with 1 kmer and 1 bytes data
+            b'K', // next section
+        ];
+
+        assert_eq!(minimizer.skip(&mut data)?, 6);
+        assert_eq!(data, b"K");
+
+        // Minimizer size read in file is checked before skip or read
+        let mut minimizer = Minimizer::new(&values)?;
+        minimizer.set_k(u64::MAX).set_m(u64::MAX);
+        let data: &[u8] = &[0; 16];
+        assert!(matches!(
+            minimizer.skip(&mut &data[..]),
+            Err(error::Error::Kff(error::Kff::LimitExceeded(_, _, _)))
+        ));
+        assert!(matches!(
+            minimizer.read_blocks(&mut &data[..]),
+            Err(error::Error::Kff(error::Kff::LimitExceeded(_, _, _)))
+        ));
+
+        minimizer.set_k(5).set_m(6);
+        assert!(matches!(
+            minimizer.skip(&mut &data[..]),
+            Err(error::Error::Kff(error::Kff::MinimizerSizeTooLarge(6, 5)))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn write() -> error::Result<()> {
         let mut values = section::Values::with_capacity(4);
@@ -263,13 +428,13 @@ mod tests {
 
         let mut writable = Vec::new();
 
-        let minimizer_val = bitvec::bitbox![u8, bitvec::order::Msb0; 0, 1, 1, 0, 1, 1, 0, 1];
+        let minimizer_val = bitvec::bitbox![u8, bitvec::order::Msb0; 1, 1, 0, 1, 0, 1, 1, 0];
         let block = section::block::Block {
             k: 5,
             data_size: 1,
             kmer: Kmer::new(
-                bitvec::bitbox![u8, bitvec::order::Msb0; 0, 0, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 1, 1, 0, 1, 1, 1],
-                vec![1, 2, 3],
+                bitvec::bitbox![u8, bitvec::order::Msb0; 0, 0, 1, 0, 1, 1, 0, 1, 1, 1, 0, 1, 0, 1, 1, 0, 1, 1],
+                vec![1, 2, 3, 4, 5],
             ),
             minimizer_offset: 4,
             offset: 0,
@@ -279,13 +444,73 @@ mod tests {
         assert_eq!(
             writable,
             vec![
-                0b01101101, // minimizer sequence
+                0b11010110, // minimizer sequence
                 0, 0, 0, 0, 0, 0, 0, 1, // number of block
-                5, 4, 0b00101101, 0b11000000, 1, 2, // kmer without minimizer
-                3, // one block with 3 kmer and 1 bytes data
+                5, 4, // one block with 5 kmer and minimizer at offset 4
+                0b00101101, 0b11000000, // kmer without minimizer
+                1, 2, 3, 4, 5, // 1 bytes data per kmer
             ]
         );
 
         Ok(())
     }
+
+    #[test]
+    fn write_invalid() -> error::Result<()> {
+        let mut values = section::Values::with_capacity(4);
+
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 3);
+        values.insert("data_size".to_string(), 1);
+
+        let minimizer = Minimizer::new(&values)?;
+        let block = |seq: &[u8], data: Vec<u8>, offset: usize| {
+            section::Block::new(5, 1, Kmer::from_ascii(seq, data, 0b00011011), offset)
+        };
+        let ct = crate::kmer::seq2bits(b"CT", 0b00011011);
+
+        let mut writable = Vec::new();
+
+        assert!(matches!(
+            minimizer.write(
+                &mut writable,
+                ct.clone(),
+                &[
+                    block(b"ACTGACT", vec![1, 2, 3], 1),
+                    block(b"ACTGACTG", vec![1, 2, 3, 4], 1)
+                ]
+            ),
+            Err(error::Error::Kff(error::Kff::BlockTooLong(4, 3)))
+        ));
+        assert!(matches!(
+            minimizer.write(&mut writable, ct.clone(), &[block(b"ACTGA", vec![1, 2], 1)]),
+            Err(error::Error::Kff(error::Kff::BadDataLength(1, 2)))
+        ));
+        assert!(matches!(
+            minimizer.write(&mut writable, ct.clone(), &[block(b"ACTGA", vec![1], 2)]),
+            Err(error::Error::Kff(error::Kff::MinimizerNotFound(2)))
+        ));
+        assert!(matches!(
+            minimizer.write(&mut writable, ct.clone(), &[block(b"ACTGA", vec![1], 4)]),
+            Err(error::Error::Kff(error::Kff::MinimizerNotFound(4)))
+        ));
+        assert!(matches!(
+            minimizer.write(&mut writable, ct, &[block(b"ACT", vec![], 1)]),
+            Err(error::Error::Kff(error::Kff::BlockTooShort(3, 5)))
+        ));
+        assert!(matches!(
+            minimizer.write(
+                &mut writable,
+                crate::kmer::seq2bits(b"CTG", 0b00011011),
+                &[]
+            ),
+            Err(error::Error::Kff(error::Kff::MinimizerSizeMismatch(2, 3)))
+        ));
+
+        // Nothing is write
+        assert!(writable.is_empty());
+
+        Ok(())
+    }
 }
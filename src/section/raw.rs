@@ -24,6 +24,9 @@ pub struct Raw {
 
     /// Size in bytes of data associate to each kmer
     data_size: u64,
+
+    /// Limits check when blocks are read
+    limits: crate::ReadLimits,
 }
 
 impl Raw {
@@ -48,6 +51,7 @@ impl Raw {
                 .get("data_size")
                 .cloned()
                 .ok_or_else(|| error::Kff::FieldIsMissing("data_size".to_string()))?,
+            limits: crate::ReadLimits::default(),
         })
     }
 
@@ -56,22 +60,93 @@ impl Raw {
     where
         R: std::io::Read + crate::KffRead,
     {
+        Ok(self.read_blocks(inner)?.into_iter().flatten().collect())
+    }
+
+    /// Read blocks of a Raw section, section flag must be already read
+    ///
+    /// Size of each block and of all blocks are check against `limits`.
+    pub fn read_blocks<R>(&self, inner: &mut R) -> error::Result<Vec<section::Block>>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        let nb_block = inner.read_u64()?;
+
         let mut output = Vec::new();
+        let mut size = 0u64;
+        for _ in 0..nb_block {
+            let block = section::block::Block::read_raw_with_limits(
+                inner,
+                self.k,
+                self.data_size as usize,
+                self.max,
+                &self.limits,
+            )?;
+
+            size = size.saturating_add(block.raw_size(self.max));
+            crate::limits::check("section size", size, *self.limits.max_section_size())?;
+
+            output.push(block);
+        }
+
+        Ok(output)
+    }
 
+    /// Skip a Raw section, section flag must be already read, return number of kmer in section
+    ///
+    /// Size of each block and of all blocks are check against `limits`.
+    pub fn skip<R>(&self, inner: &mut R) -> error::Result<u64>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
         let nb_block = inner.read_u64()?;
 
+        let mut nb_kmer = 0;
+        let mut size = 0u64;
         for _ in 0..nb_block {
-            let block =
-                section::block::Block::read_raw(inner, self.k, self.data_size as usize, self.max)?;
+            let (block_kmer, block_size) = section::block::Block::skip_raw_with_limits(
+                inner,
+                self.k,
+                self.data_size as usize,
+                self.max,
+                &self.limits,
+            )?;
+            nb_kmer += block_kmer;
+
+            size = size.saturating_add(block_size);
+            crate::limits::check("section size", size, *self.limits.max_section_size())?;
+        }
+
+        Ok(nb_kmer)
+    }
 
-            output.extend(block);
+    /// Check all blocks can be write in this section, see [Block::validate](section::Block::validate)
+    pub fn validate(&self, blocks: &[section::block::Block]) -> error::Result<()> {
+        for block in blocks {
+            block.validate(self.k, self.data_size, self.max, None)?;
         }
 
-        Ok(output)
+        Ok(())
     }
 
     /// Write a Raw section, section flag isn't write
+    ///
+    /// Blocks are checked with [Raw::validate] before any write.
     pub fn write<W>(&self, outer: &mut W, blocks: &[section::block::Block]) -> error::Result<()>
+    where
+        W: std::io::Write + crate::KffWrite,
+    {
+        self.validate(blocks)?;
+
+        self.write_unchecked(outer, blocks)
+    }
+
+    /// Write a Raw section without check blocks, they must be validated before
+    pub(crate) fn write_unchecked<W>(
+        &self,
+        outer: &mut W,
+        blocks: &[section::block::Block],
+    ) -> error::Result<()>
     where
         W: std::io::Write + crate::KffWrite,
     {
@@ -163,6 +238,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn skip() -> error::Result<()> {
+        let mut values = section::Values::with_capacity(4);
+
+        values.insert("k".to_string(), 5);
+        values.insert("ordered".to_string(), false as u64);
+        values.insert("max".to_string(), 255);
+        values.insert("data_size".to_string(), 1);
+
+        let raw = Raw::new(&values)?;
+
+        let mut data: &[u8] = &[
+            0, 0, 0, 0, 0, 0, 0, 3, // number of block
+            3, 0b00011011, 0b11110100, 1, 2, 3, // one block with 3 kmer and 1 bytes data
+            2, 0b00011011, 0b11110000, 1, 2, // one block with 2 kmer and 1 bytes data
+            1, 0b00011011, 0b11000000, 1,    // one block with 1 kmer and 1 bytes data
+            b'K', // next section
+        ];
+
+        assert_eq!(raw.skip(&mut data)?, 6);
+        assert_eq!(data, b"K");
+
+        Ok(())
+    }
+
     #[test]
     fn write() -> error::Result<()> {
         let mut values = section::Values::with_capacity(4);
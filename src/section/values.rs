@@ -24,6 +24,11 @@ pub trait AbcValues: Sized {
     where
         R: std::io::Read + crate::KffRead;
 
+    /// Same as [AbcValues::read] but number of variables is check against `limits`
+    fn read_with_limits<R>(inner: &mut R, limits: &crate::ReadLimits) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead;
+
     /// Write contents of Values in writables, section flag isn't write
     fn write<W>(&self, outer: &mut W) -> error::Result<()>
     where
@@ -33,6 +38,9 @@ pub trait AbcValues: Sized {
     fn write_as_footer<W>(&self, outer: &mut W) -> error::Result<()>
     where
         W: std::io::Write + crate::KffWrite;
+
+    /// Get value of variable `name`, if it's absent return [error::Kff::FieldIsMissing]
+    fn get_value(&self, name: &str) -> error::Result<u64>;
 }
 
 impl AbcValues for Values {
@@ -48,11 +56,20 @@ impl AbcValues for Values {
     }
 
     fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        Self::read_with_limits(inner, &crate::ReadLimits::default())
+    }
+
+    fn read_with_limits<R>(inner: &mut R, limits: &crate::ReadLimits) -> error::Result<Self>
     where
         R: std::io::Read + crate::KffRead,
     {
         let nb_variable = inner.read_u64()?;
-        let mut obj = Self::with_capacity(nb_variable as usize);
+        crate::limits::check("number of variables", nb_variable, *limits.max_variables())?;
+
+        let mut obj = Self::with_capacity(std::cmp::min(nb_variable, 64) as usize);
 
         for _ in 0..nb_variable {
             let key = String::from_utf8(inner.read_ascii()?)?;
@@ -99,6 +116,12 @@ impl AbcValues for Values {
 
         Ok(())
     }
+
+    fn get_value(&self, name: &str) -> error::Result<u64> {
+        self.get(name)
+            .cloned()
+            .ok_or_else(|| error::Kff::FieldIsMissing(name.to_string()).into())
+    }
 }
 
 #[cfg(test)]
@@ -147,6 +170,16 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn read_truncated() -> error::Result<()> {
+        // Number of variable is larger than input
+        let mut input_file: &[u8] = &[255, 255, 255, 255, 255, 255, 255, 255, 107, 0];
+
+        assert!(Values::read(&mut input_file).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn write() -> error::Result<()> {
         let mut values = Values::with_capacity(20);
@@ -203,4 +236,18 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn get_value() -> error::Result<()> {
+        let mut values = Values::new();
+        values.insert("k".to_string(), 15);
+
+        assert_eq!(values.get_value("k")?, 15);
+        assert!(matches!(
+            values.get_value("max"),
+            Err(error::Error::Kff(error::Kff::FieldIsMissing(name))) if name == "max"
+        ));
+
+        Ok(())
+    }
 }
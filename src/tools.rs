@@ -0,0 +1,41 @@
+//! High level operations on whole KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+
+/* mod declaration */
+pub mod bloom;
+pub mod compact;
+pub mod concat;
+pub mod edit;
+pub mod fasta;
+pub mod filter;
+pub mod from_reads;
+pub mod kmc;
+pub mod mphf;
+pub mod query;
+pub mod recover;
+pub mod sort;
+pub mod split;
+pub mod stats;
+pub mod text;
+
+/* pub use */
+pub use bloom::KmerBloom;
+pub use compact::compact;
+pub use concat::concat;
+pub use edit::Editor;
+pub use fasta::to_fasta;
+pub use filter::KffFilter;
+pub use from_reads::from_reads;
+pub use kmc::from_kmc;
+pub use mphf::KmerMap;
+pub use query::query;
+pub use recover::Recovery;
+pub use sort::sort;
+pub use split::split;
+pub use stats::Stats;
+pub use text::{from_text, to_text};
@@ -0,0 +1,446 @@
+//! Approximate membership of kmers with a bloom filter
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::Kff;
+use crate::Seq2Bit;
+
+/// Magic number at begin of a saved bloom filter
+const MAGIC: &[u8; 4] = b"KFFB";
+
+/// Bloom filter of kmers
+///
+/// Each kmer is hashed once, `nb_hash` positions are derived from this hash by double hashing.
+/// If filter is canonical, kmers are replaced by minimum of kmer and its reverse complement in 2
+/// bits before insertion and query. Filter is saved with `KFFB`, `k`, encoding, canonical flag,
+/// number of hash, number of bits and bits as big endian 64 bits words.
+#[derive(
+    getset::Getters, std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Fields")
+)]
+#[getset(get = "pub")]
+pub struct KmerBloom {
+    /// Size of kmer used by sequence queries
+    k: u64,
+
+    /// Encoding of kmers
+    encoding: u8,
+
+    /// Kmers are canonicalized
+    canonical: bool,
+
+    /// Number of hash function
+    nb_hash: u64,
+
+    /// Number of bits
+    nb_bits: u64,
+
+    /// Bits of filter
+    #[getset(skip)]
+    bits: Vec<u64>,
+}
+
+/// Fields of a deserialized KmerBloom, checked like by [KmerBloom::read] before use
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Fields {
+    k: u64,
+    encoding: u8,
+    canonical: bool,
+    nb_hash: u64,
+    nb_bits: u64,
+    bits: Vec<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<Fields> for KmerBloom {
+    type Error = error::Error;
+
+    fn try_from(fields: Fields) -> error::Result<Self> {
+        let nb_words = nb_words(fields.nb_hash, fields.nb_bits)?;
+        if fields.bits.len() as u64 != nb_words {
+            return Err(error::Kff::BadKmerBloom(format!(
+                "{} words of bits but {} are expected",
+                fields.bits.len(),
+                nb_words
+            ))
+            .into());
+        }
+
+        Ok(Self {
+            k: fields.k,
+            encoding: fields.encoding,
+            canonical: fields.canonical,
+            nb_hash: fields.nb_hash,
+            nb_bits: fields.nb_bits,
+            bits: fields.bits,
+        })
+    }
+}
+
+/// Check number of hash and number of bits, return number of 64 bits words of bits
+fn nb_words(nb_hash: u64, nb_bits: u64) -> error::Result<u64> {
+    if nb_hash == 0 {
+        return Err(error::Kff::BadKmerBloom("number of hash is 0".to_string()).into());
+    }
+
+    match nb_bits.checked_add(63) {
+        Some(bits) if nb_bits != 0 => Ok(bits / 64),
+        _ => {
+            Err(error::Kff::BadKmerBloom(format!("number of bits {} isn't valid", nb_bits)).into())
+        }
+    }
+}
+
+impl KmerBloom {
+    /// Create an empty filter sized for `nb_kmer` kmers with a false positive rate `fpr`
+    pub fn new(
+        k: u64,
+        encoding: u8,
+        canonical: bool,
+        nb_kmer: u64,
+        fpr: f64,
+    ) -> error::Result<Self> {
+        if !(fpr > 0.0 && fpr < 1.0) {
+            return Err(error::Kff::FalsePositiveRate(fpr).into());
+        }
+
+        let ln2 = std::f64::consts::LN_2;
+        let nb_bits = ((-(nb_kmer.max(1) as f64) * fpr.ln() / (ln2 * ln2)).ceil() as u64).max(64);
+        let nb_hash = ((nb_bits as f64 / nb_kmer.max(1) as f64) * ln2)
+            .round()
+            .max(1.0) as u64;
+
+        Ok(Self {
+            k,
+            encoding,
+            canonical,
+            nb_hash,
+            nb_bits,
+            bits: vec![0; ((nb_bits + 63) / 64) as usize],
+        })
+    }
+
+    /// Insert a kmer
+    pub fn insert(&mut self, kmer: &Seq2Bit) {
+        self.insert_hash(crate::kmer::hash(kmer, self.encoding, self.canonical))
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        for position in self.positions(hash) {
+            self.bits[(position / 64) as usize] |= 1 << (position % 64);
+        }
+    }
+
+    /// Return true if kmer is maybe present, false if kmer is absent
+    pub fn contains(&self, kmer: &Seq2Bit) -> bool {
+        self.positions(crate::kmer::hash(kmer, self.encoding, self.canonical))
+            .all(|position| self.bits[(position / 64) as usize] & (1 << (position % 64)) != 0)
+    }
+
+    /// For each kmer of size `k` of sequence, return true if kmer is maybe present
+    ///
+    /// A kmer containing a character other than A, C, G or T (case insensitive) is absent, a
+    /// sequence shorter than `k` give an empty vector.
+    pub fn query_sequence(&self, seq: &[u8]) -> Vec<bool> {
+        if self.k == 0 || seq.len() < self.k as usize {
+            return Vec::new();
+        }
+
+        seq.windows(self.k as usize)
+            .map(|window| {
+                window.iter().all(|nuc| b"ACGTacgt".contains(nuc))
+                    && self.contains(&crate::kmer::seq2bits(window, self.encoding))
+            })
+            .collect()
+    }
+
+    /// Read a KmerBloom
+    pub fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        if &inner.read_n_bytes::<4>()? != MAGIC {
+            return Err(error::Kff::NotAKmerBloom.into());
+        }
+
+        let k = inner.read_u64()?;
+        let encoding = inner.read_u8()?;
+        let canonical = inner.read_bool()?;
+        let nb_hash = inner.read_u64()?;
+        let nb_bits = inner.read_u64()?;
+
+        let nb_words = nb_words(nb_hash, nb_bits)?;
+
+        let bits = (0..nb_words)
+            .map(|_| inner.read_u64())
+            .collect::<error::Result<Vec<u64>>>()?;
+
+        Ok(Self {
+            k,
+            encoding,
+            canonical,
+            nb_hash,
+            nb_bits,
+            bits,
+        })
+    }
+
+    /// Write a KmerBloom
+    pub fn write<W>(&self, outer: &mut W) -> error::Result<()>
+    where
+        W: std::io::Write + crate::KffWrite,
+    {
+        outer.write_bytes(MAGIC)?;
+        outer.write_u64(&self.k)?;
+        outer.write_u8(&self.encoding)?;
+        outer.write_bool(&self.canonical)?;
+        outer.write_u64(&self.nb_hash)?;
+        outer.write_u64(&self.nb_bits)?;
+
+        for word in self.bits.iter() {
+            outer.write_u64(word)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a KmerBloom from file match with path
+    pub fn load<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut inner = std::io::BufReader::new(std::fs::File::open(path)?);
+
+        Self::read(&mut inner)
+    }
+
+    /// Write a KmerBloom in file match with path
+    pub fn save<P>(&self, path: P) -> error::Result<()>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut outer = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+        self.write(&mut outer)?;
+        std::io::Write::flush(&mut outer)?;
+
+        Ok(())
+    }
+
+    /// Get positions of a kmer hash in bits
+    fn positions(&self, hash: u64) -> impl Iterator<Item = u64> {
+        let (h1, h2) = (hash, hash.rotate_left(32) | 1);
+        let nb_bits = self.nb_bits;
+
+        (0..self.nb_hash).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % nb_bits)
+    }
+}
+
+impl<R> Kff<R>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+{
+    /// Insert all kmers in a [KmerBloom] with a false positive rate `fpr`
+    ///
+    /// Filter is canonical if header canonical flag is set and `k` is size of first kmer. Hash
+    /// of each kmer is kept in memory to size filter.
+    pub fn build_bloom(self, fpr: f64) -> error::Result<KmerBloom> {
+        if !(fpr > 0.0 && fpr < 1.0) {
+            return Err(error::Kff::FalsePositiveRate(fpr).into());
+        }
+
+        let encoding = *self.header().encoding();
+        let canonical = *self.header().canonical_kmer();
+
+        let mut k = 0;
+        let mut hashes = Vec::new();
+        for kmer in self.kmers() {
+            let kmer = kmer?;
+
+            if hashes.is_empty() {
+                k = kmer.seq2bit().len() as u64 / 2;
+            }
+            hashes.push(crate::kmer::hash(kmer.seq2bit(), encoding, canonical));
+        }
+
+        let mut bloom = KmerBloom::new(k, encoding, canonical, hashes.len() as u64, fpr)?;
+        for hash in hashes {
+            bloom.insert_hash(hash);
+        }
+
+        Ok(bloom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section;
+    use crate::section::values::AbcValues as _;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file(canonical: bool, kmers: &[&[u8]]) -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), 0);
+        writer.write_values(values.clone()).unwrap();
+
+        let blocks: Vec<section::Block> = kmers
+            .iter()
+            .map(|seq| section::Block::new(5, 0, Kmer::from_ascii(seq, vec![], ENCODING), 0))
+            .collect();
+        writer
+            .write_raw(section::Raw::new(&values).unwrap(), &blocks)
+            .unwrap();
+        writer.finalize().unwrap();
+
+        writer.into_inner()
+    }
+
+    fn random_kmers(rng: &mut rand::rngs::StdRng, nb: usize) -> Vec<Vec<u8>> {
+        use rand::Rng as _;
+
+        (0..nb)
+            .map(|_| (0..21).map(|_| b"ACGT"[rng.gen_range(0..4)]).collect())
+            .collect()
+    }
+
+    #[test]
+    fn build() -> error::Result<()> {
+        let input = file(false, &[b"ACTGA", b"TTTTT"]);
+        let bloom = Kff::read(std::io::Cursor::new(input))?.build_bloom(0.01)?;
+
+        assert_eq!(bloom.k(), &5);
+        assert!(!bloom.canonical());
+        assert!(bloom.contains(&crate::kmer::seq2bits(b"ACTGA", ENCODING)));
+        assert!(bloom.contains(&crate::kmer::seq2bits(b"TTTTT", ENCODING)));
+
+        // Kmers with N are absent
+        let coverage = bloom.query_sequence(b"ACTGANTTTTT");
+        assert_eq!(coverage.len(), 7);
+        assert!(coverage[0] && coverage[6]);
+        assert!(!coverage[1] && !coverage[5]);
+        assert!(bloom.query_sequence(b"ACT").is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical() -> error::Result<()> {
+        let input = file(true, &[b"ACTGA"]);
+        let bloom = Kff::read(std::io::Cursor::new(input))?.build_bloom(0.01)?;
+
+        assert!(bloom.canonical());
+        assert!(bloom.contains(&crate::kmer::seq2bits(b"TCAGT", ENCODING)));
+        assert_eq!(bloom.query_sequence(b"tcagt"), vec![true]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn false_positive_rate() -> error::Result<()> {
+        use rand::SeedableRng as _;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut bloom = KmerBloom::new(21, ENCODING, false, 1000, 0.01)?;
+        for kmer in random_kmers(&mut rng, 1000) {
+            bloom.insert(&crate::kmer::seq2bits(&kmer, ENCODING));
+        }
+
+        let false_positive = random_kmers(&mut rng, 10000)
+            .iter()
+            .filter(|kmer| bloom.contains(&crate::kmer::seq2bits(kmer, ENCODING)))
+            .count();
+        assert!(false_positive < 300, "{} false positives", false_positive);
+
+        assert!(matches!(
+            KmerBloom::new(21, ENCODING, false, 1000, 1.0),
+            Err(error::Error::Kff(error::Kff::FalsePositiveRate(_)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_write() -> error::Result<()> {
+        let input = file(false, &[b"ACTGA", b"TTTTT"]);
+        let bloom = Kff::read(std::io::Cursor::new(input))?.build_bloom(0.05)?;
+
+        let mut buffer = Vec::new();
+        bloom.write(&mut buffer)?;
+        assert_eq!(&buffer[..4], b"KFFB");
+
+        assert_eq!(
+            KmerBloom::read(&mut std::io::Cursor::new(buffer.clone()))?,
+            bloom
+        );
+
+        assert!(matches!(
+            KmerBloom::read(&mut std::io::Cursor::new(b"KFFI".to_vec())),
+            Err(error::Error::Kff(error::Kff::NotAKmerBloom))
+        ));
+
+        // Number of hash, then number of bits, are the last fields before bits
+        let header = 4 + 8 + 1 + 1;
+        let mut bad = buffer.clone();
+        bad[header..header + 8].copy_from_slice(&0u64.to_be_bytes());
+        assert!(matches!(
+            KmerBloom::read(&mut std::io::Cursor::new(bad)),
+            Err(error::Error::Kff(error::Kff::BadKmerBloom(_)))
+        ));
+
+        for nb_bits in [0, u64::MAX] {
+            let mut bad = buffer.clone();
+            bad[header + 8..header + 16].copy_from_slice(&nb_bits.to_be_bytes());
+            assert!(matches!(
+                KmerBloom::read(&mut std::io::Cursor::new(bad)),
+                Err(error::Error::Kff(error::Kff::BadKmerBloom(_)))
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize() -> error::Result<()> {
+        let input = file(false, &[b"ACTGA", b"TTTTT"]);
+        let bloom = Kff::read(std::io::Cursor::new(input))?.build_bloom(0.05)?;
+
+        let json = serde_json::to_value(&bloom).map_err(std::io::Error::from)?;
+        assert_eq!(
+            serde_json::from_value::<KmerBloom>(json.clone()).map_err(std::io::Error::from)?,
+            bloom
+        );
+
+        // Same checks as read
+        for (field, value) in [
+            ("nb_hash", serde_json::json!(0)),
+            ("nb_bits", serde_json::json!(0)),
+            ("nb_bits", serde_json::json!(u64::MAX)),
+            ("bits", serde_json::json!([])),
+        ] {
+            let mut bad = json.clone();
+            bad[field] = value;
+            assert!(serde_json::from_value::<KmerBloom>(bad).is_err());
+        }
+
+        Ok(())
+    }
+}
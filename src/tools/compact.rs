@@ -0,0 +1,474 @@
+//! Re-pack kmers of a KFF file in maximal super-kmer
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::section::values::AbcValues as _;
+use crate::Kff;
+use crate::Kmer;
+use crate::Seq2Bit;
+
+/// Summary of a compaction
+#[derive(
+    getset::Getters,
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::default::Default,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+)]
+#[getset(get = "pub")]
+pub struct Report {
+    /// Number of block read
+    input_blocks: u64,
+
+    /// Number of block write
+    output_blocks: u64,
+
+    /// Number of bytes used by kmer sections in input
+    input_size: u64,
+
+    /// Number of bytes used by kmer sections in output
+    output_size: u64,
+}
+
+impl Report {
+    /// Number of bytes saved by compaction, negative if output is larger
+    pub fn saving(&self) -> i64 {
+        self.input_size as i64 - self.output_size as i64
+    }
+}
+
+/// Read all kmer sections of `input`, merge overlapping kmers in blocks and write them in `outer`
+///
+/// Each block contains at most `max` kmers, if `max` is None the max value of each section is kept.
+/// Kmers are merged only with kmers of the same section, section type is kept. If file is
+/// canonical, kmers of Raw sections are also merged through their reverse complement. In Minimizer
+/// sections, reverse complement of a kmer doesn't contain section minimizer, so only forward
+/// overlaps are merged.
+pub fn compact<R, W>(mut input: Kff<R>, outer: W, max: Option<u64>) -> error::Result<Report>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+    W: std::io::Write + crate::KffWrite,
+{
+    let mut output = Kff::write(outer, input.header().clone())?;
+    let rev_comp = if *input.header().canonical_kmer() {
+        Some(*input.header().encoding())
+    } else {
+        None
+    };
+    let mut report = Report::default();
+    let mut last_values: Option<section::Values> = None;
+
+    while let Some(section) = input.next_block_section() {
+        let section = section?;
+
+        let input_max = input.values().get_value("max")?;
+        let mut values = input.values().clone();
+        if let Some(max) = max {
+            values.insert("max".to_string(), max);
+        }
+        if values.contains_key("ordered") {
+            values.insert("ordered".to_string(), false as u64);
+        }
+
+        let k = values.get_value("k")?;
+        let output_max = values.get_value("max")?;
+
+        if last_values.as_ref() != Some(&values) {
+            output.write_values(values.clone())?;
+            last_values = Some(values.clone());
+        }
+
+        report.input_blocks += section.blocks().len() as u64;
+        report.input_size += section.size(input_max);
+
+        let compacted = match section {
+            section::KmerSection::Raw(blocks) => {
+                section::KmerSection::Raw(super_kmers(blocks, k, output_max, None, rev_comp))
+            }
+            section::KmerSection::Minimizer(minimizer, blocks) => {
+                let blocks = super_kmers(blocks, k, output_max, Some(&minimizer), None);
+                section::KmerSection::Minimizer(minimizer, blocks)
+            }
+        };
+
+        report.output_blocks += compacted.blocks().len() as u64;
+        report.output_size += compacted.size(output_max);
+
+        match compacted {
+            section::KmerSection::Raw(blocks) => {
+                output.write_raw(section::Raw::new(&values)?, &blocks)?
+            }
+            section::KmerSection::Minimizer(minimizer, blocks) => {
+                output.write_minimizer(section::Minimizer::new(&values)?, minimizer, &blocks)?
+            }
+        }
+    }
+
+    output.finalize()?;
+
+    Ok(report)
+}
+
+/// Greedily assemble kmers of blocks in super-kmer with at most `max` kmers
+///
+/// If a minimizer is given, all kmers of a super-kmer share the same minimizer occurrence. If
+/// `rev_comp` contains an encoding, kmers can also be joined through their reverse complement,
+/// only without minimizer.
+pub(crate) fn super_kmers(
+    blocks: Vec<section::Block>,
+    k: u64,
+    max: u64,
+    minimizer: Option<&Seq2Bit>,
+    rev_comp: Option<u8>,
+) -> Vec<section::Block> {
+    let k = k as usize;
+    let data_size = blocks.first().map(|b| *b.data_size()).unwrap_or(0);
+
+    // Each kmer with minimizer position relative to kmer start
+    let mut kmers: Vec<(Kmer, usize)> = Vec::new();
+    for block in blocks {
+        let minimizer_offset = *block.minimizer_offset();
+        for (i, kmer) in block.enumerate() {
+            kmers.push((kmer, minimizer_offset.saturating_sub(i)));
+        }
+    }
+
+    let mut index: rustc_hash::FxHashMap<Seq2Bit, Vec<usize>> = rustc_hash::FxHashMap::default();
+    for (i, (kmer, _)) in kmers.iter().enumerate() {
+        index.entry(kmer.seq2bit().clone()).or_default().push(i);
+    }
+
+    let span = minimizer.map(|m| (k - m.len() / 2) as i64);
+    let rev_comp = if span.is_none() { rev_comp } else { None };
+    let mut used = vec![false; kmers.len()];
+    let mut output = Vec::new();
+
+    for first in 0..kmers.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+
+        // Kmer id and if it's used as reverse complement
+        let mut path = std::collections::VecDeque::from([(first, false)]);
+        let mut start: i64 = 0;
+        let mut end: i64 = 0;
+        let minimizer_pos = kmers[first].1 as i64;
+
+        // Extend on right
+        while (path.len() as u64) < max && span.map_or(true, |_| end < minimizer_pos) {
+            let last = oriented(&kmers, *path.back().unwrap(), rev_comp);
+            let mut candidate = bitvec::vec::BitVec::from_bitslice(&last[2..]);
+            candidate.extend_from_bitslice(bitvec::bits![u8, bitvec::order::Msb0; 0, 0]);
+
+            match take_neighbor(&index, &mut used, candidate, k * 2 - 2, rev_comp) {
+                Some(next) => {
+                    path.push_back(next);
+                    end += 1;
+                }
+                None => break,
+            }
+        }
+
+        // Extend on left
+        while (path.len() as u64) < max && span.map_or(true, |s| minimizer_pos < start + s) {
+            let head = oriented(&kmers, *path.front().unwrap(), rev_comp);
+            let mut candidate = bitvec::bitvec![u8, bitvec::order::Msb0; 0, 0];
+            candidate.extend_from_bitslice(&head[..k * 2 - 2]);
+
+            match take_neighbor(&index, &mut used, candidate, 0, rev_comp) {
+                Some(prev) => {
+                    path.push_front(prev);
+                    start -= 1;
+                }
+                None => break,
+            }
+        }
+
+        let mut seq = bitvec::vec::BitVec::from_bitslice(&oriented(&kmers, path[0], rev_comp));
+        let mut data = Vec::with_capacity(path.len() * data_size);
+        for (i, node) in path.iter().enumerate() {
+            if i != 0 {
+                seq.extend_from_bitslice(&oriented(&kmers, *node, rev_comp)[k * 2 - 2..]);
+            }
+            data.extend_from_slice(kmers[node.0].0.data());
+        }
+
+        output.push(section::Block::new(
+            k as u64,
+            data_size,
+            Kmer::new(seq.into_boxed_bitslice(), data),
+            span.map_or(0, |_| (minimizer_pos - start) as usize),
+        ));
+    }
+
+    output
+}
+
+/// Sequence of a kmer of path, in path orientation
+fn oriented(
+    kmers: &[(Kmer, usize)],
+    (id, reverse): (usize, bool),
+    rev_comp: Option<u8>,
+) -> Seq2Bit {
+    match (reverse, rev_comp) {
+        (true, Some(encoding)) => crate::kmer::rev_comp(kmers[id].0.seq2bit(), encoding),
+        _ => kmers[id].0.seq2bit().clone(),
+    }
+}
+
+/// Try each nucleotide at position `nuc` (in bits) of candidate and mark first unused match as used
+///
+/// If `rev_comp` contains an encoding, a kmer equal to reverse complement of candidate match too.
+fn take_neighbor(
+    index: &rustc_hash::FxHashMap<Seq2Bit, Vec<usize>>,
+    used: &mut [bool],
+    mut candidate: bitvec::vec::BitVec<u8, bitvec::order::Msb0>,
+    nuc: usize,
+    rev_comp: Option<u8>,
+) -> Option<(usize, bool)> {
+    for bits in 0..4 {
+        candidate.set(nuc, bits & 0b10 != 0);
+        candidate.set(nuc + 1, bits & 0b01 != 0);
+
+        if let Some(id) = take_unused(index, used, candidate.as_bitslice()) {
+            return Some((id, false));
+        }
+
+        if let Some(encoding) = rev_comp {
+            let reverse = crate::kmer::rev_comp(&candidate.clone().into_boxed_bitslice(), encoding);
+            if let Some(id) = take_unused(index, used, &reverse) {
+                return Some((id, true));
+            }
+        }
+    }
+
+    None
+}
+
+/// Mark first unused kmer equal to `seq` as used
+fn take_unused(
+    index: &rustc_hash::FxHashMap<Seq2Bit, Vec<usize>>,
+    used: &mut [bool],
+    seq: &bitvec::slice::BitSlice<u8, bitvec::order::Msb0>,
+) -> Option<usize> {
+    let id = *index.get(seq)?.iter().find(|id| !used[**id])?;
+    used[id] = true;
+
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn build(
+        values: &section::Values,
+        minimizer: Option<&[u8]>,
+        kmers: &[(&[u8], usize)],
+        canonical: bool,
+    ) -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+        writer.write_values(values.clone()).unwrap();
+
+        let blocks: Vec<section::Block> = kmers
+            .iter()
+            .enumerate()
+            .map(|(i, (seq, offset))| {
+                section::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(seq, vec![i as u8], ENCODING),
+                    *offset,
+                )
+            })
+            .collect();
+
+        match minimizer {
+            None => writer
+                .write_raw(section::Raw::new(values).unwrap(), &blocks)
+                .unwrap(),
+            Some(m) => writer
+                .write_minimizer(
+                    section::Minimizer::new(values).unwrap(),
+                    crate::kmer::seq2bits(m, ENCODING),
+                    &blocks,
+                )
+                .unwrap(),
+        }
+        writer.finalize().unwrap();
+
+        writer.inner().clone()
+    }
+
+    fn values(max: u64) -> section::Values {
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 3);
+        values.insert("max".to_string(), max);
+        values.insert("data_size".to_string(), 1);
+
+        values
+    }
+
+    #[test]
+    fn raw() -> error::Result<()> {
+        let input = build(
+            &values(1),
+            None,
+            &[
+                (b"ACTGA", 0),
+                (b"CTGAC", 0),
+                (b"TGACT", 0),
+                (b"GACTT", 0),
+                (b"ACTTG", 0),
+                (b"CTTGA", 0),
+                (b"TTGAC", 0),
+            ],
+            false,
+        );
+
+        let mut output = Vec::new();
+        let report = compact(
+            Kff::read(std::io::Cursor::new(input))?,
+            &mut output,
+            Some(255),
+        )?;
+
+        assert_eq!(report.input_blocks(), &7);
+        assert_eq!(report.output_blocks(), &1);
+        assert_eq!(report.input_size(), &30);
+        assert_eq!(report.output_size(), &20);
+        assert_eq!(report.saving(), 10);
+
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+        let section = reader.next_block_section().unwrap()?;
+        assert_eq!(reader.values().get("max"), Some(&255));
+
+        let blocks = section.into_blocks();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].kmer().seq(ENCODING), b"ACTGACTTGAC");
+        assert_eq!(blocks[0].kmer().data(), &vec![0, 1, 2, 3, 4, 5, 6]);
+
+        assert!(reader.next_block_section().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_keep_max() -> error::Result<()> {
+        let input = build(
+            &values(2),
+            None,
+            &[(b"ACTGA", 0), (b"CTGAC", 0), (b"TGACT", 0)],
+            false,
+        );
+
+        let mut output = Vec::new();
+        let report = compact(Kff::read(std::io::Cursor::new(input))?, &mut output, None)?;
+
+        assert_eq!(report.input_blocks(), &3);
+        assert_eq!(report.output_blocks(), &2);
+
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+        let kmers: Vec<Vec<u8>> = reader
+            .next_kmer_section()
+            .unwrap()?
+            .iter()
+            .map(|k| k.seq(ENCODING))
+            .collect();
+
+        assert_eq!(
+            kmers,
+            vec![b"ACTGA".to_vec(), b"CTGAC".to_vec(), b"TGACT".to_vec()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_canonical() -> error::Result<()> {
+        // GTCAG is reverse complement of CTGAC
+        let kmers: &[(&[u8], usize)] = &[(b"ACTGA", 0), (b"GTCAG", 0), (b"TGACT", 0)];
+
+        let mut output = Vec::new();
+        let input = build(&values(1), None, kmers, false);
+        let report = compact(
+            Kff::read(std::io::Cursor::new(input))?,
+            &mut output,
+            Some(10),
+        )?;
+        assert_eq!(report.output_blocks(), &3);
+
+        let mut output = Vec::new();
+        let input = build(&values(1), None, kmers, true);
+        let report = compact(
+            Kff::read(std::io::Cursor::new(input))?,
+            &mut output,
+            Some(10),
+        )?;
+        assert_eq!(report.output_blocks(), &1);
+
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+        let blocks = reader.next_block_section().unwrap()?.into_blocks();
+        assert_eq!(blocks[0].kmer().seq(ENCODING), b"ACTGACT");
+        assert_eq!(blocks[0].kmer().data(), &vec![0, 1, 2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn minimizer() -> error::Result<()> {
+        // Reverse complement isn't used in Minimizer sections, result is the same for canonical file
+        for canonical in [false, true] {
+            minimizer_(canonical)?;
+        }
+
+        Ok(())
+    }
+
+    fn minimizer_(canonical: bool) -> error::Result<()> {
+        let input = build(
+            &values(1),
+            Some(b"AAA"),
+            &[(b"TGAAA", 2), (b"GAAAC", 1), (b"AAACT", 0), (b"AAACG", 0)],
+            canonical,
+        );
+
+        let mut output = Vec::new();
+        let report = compact(
+            Kff::read(std::io::Cursor::new(input))?,
+            &mut output,
+            Some(100),
+        )?;
+
+        assert_eq!(report.input_blocks(), &4);
+        assert_eq!(report.output_blocks(), &2);
+
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+        let section = reader.next_block_section().unwrap()?;
+
+        assert_eq!(
+            section.minimizer(),
+            Some(&crate::kmer::seq2bits(b"AAA", ENCODING))
+        );
+
+        let blocks = section.into_blocks();
+        assert_eq!(blocks[0].kmer().seq(ENCODING), b"TGAAACT");
+        assert_eq!(blocks[0].minimizer_offset(), &2);
+        assert_eq!(blocks[1].kmer().seq(ENCODING), b"AAACG");
+        assert_eq!(blocks[1].minimizer_offset(), &0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! Concatenate KFF files without decoding kmers
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::Kff;
+
+/// Concatenate kmer sections of all `inputs` in `outer`
+///
+/// Header of first input is used, all inputs must have the same encoding and canonical flag. Two
+/// files can contain the same kmer, so if there are many inputs the uniq flag is cleared. Bytes of
+/// kmer sections are copied verbatim, Values sections are written only when needed and an index
+/// and a footer are written at end.
+pub fn concat<I, R, W>(inputs: I, outer: W) -> error::Result<()>
+where
+    I: std::iter::IntoIterator<Item = Kff<R>>,
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+    W: std::io::Write + crate::KffWrite,
+{
+    let mut inputs = inputs.into_iter().peekable();
+    let first = match inputs.next() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+
+    let mut header = first.header().clone();
+    if inputs.peek().is_some() {
+        header.set_uniq_kmer(false);
+    }
+    let mut writer = Kff::write(outer, header)?;
+    let mut last_values = None;
+
+    for mut input in std::iter::once(first).chain(inputs) {
+        if !compatible(writer.header(), input.header()) {
+            return Err(error::Kff::HeaderNotCompatible.into());
+        }
+
+        while let Some(section) = input.next_section_bytes() {
+            let (section_type, bytes) = section?;
+
+            if last_values.as_ref() != Some(input.values()) {
+                writer.write_values(input.values().clone())?;
+                last_values = Some(input.values().clone());
+            }
+
+            writer.write_section_bytes(section_type, &bytes)?;
+        }
+    }
+
+    writer.finalize_with_index()
+}
+
+/// Check if two headers could be merged, uniq flag isn't checked
+pub fn compatible(a: &crate::section::Header, b: &crate::section::Header) -> bool {
+    a.encoding() == b.encoding() && a.canonical_kmer() == b.canonical_kmer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section;
+    use crate::section::values::AbcValues as _;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file(k: u64, canonical: bool, minimizer: bool, seq: &[u8]) -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), k);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 255);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone()).unwrap();
+
+        let nb_kmer = seq.len() - k as usize + 1;
+        let block = section::Block::new(k, 1, Kmer::from_ascii(seq, vec![7; nb_kmer], ENCODING), 0);
+
+        if minimizer {
+            writer
+                .write_minimizer(
+                    section::Minimizer::new(&values).unwrap(),
+                    crate::kmer::seq2bits(&seq[..2], ENCODING),
+                    &[block],
+                )
+                .unwrap();
+        } else {
+            writer
+                .write_raw(section::Raw::new(&values).unwrap(), &[block])
+                .unwrap();
+        }
+        writer.finalize_with_index().unwrap();
+
+        writer.into_inner()
+    }
+
+    fn kff(data: Vec<u8>) -> Kff<std::io::Cursor<Vec<u8>>> {
+        Kff::read(std::io::Cursor::new(data)).unwrap()
+    }
+
+    #[test]
+    fn merge() -> error::Result<()> {
+        let mut output = Vec::new();
+
+        concat(
+            vec![
+                kff(file(5, true, false, b"ACTGAC")),
+                kff(file(5, true, true, b"GGTTA")),
+                kff(file(3, true, false, b"TTTA")),
+            ],
+            &mut output,
+        )?;
+
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(output))?;
+        let types: Vec<u8> = reader
+            .index()
+            .as_ref()
+            .unwrap()
+            .pair()
+            .iter()
+            .map(|(t, _)| *t)
+            .collect();
+        assert_eq!(types, b"vrmvr");
+
+        let mut kmers = Vec::new();
+        while let Some(section) = reader.next_kmer_section() {
+            kmers.extend(section?.iter().map(|k| k.seq(ENCODING)));
+        }
+
+        assert_eq!(
+            kmers,
+            vec![
+                b"ACTGA".to_vec(),
+                b"CTGAC".to_vec(),
+                b"GGTTA".to_vec(),
+                b"TTT".to_vec(),
+                b"TTA".to_vec()
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn uniq() -> error::Result<()> {
+        // Both files are uniq but share kmer ACTGA
+        let mut output = Vec::new();
+        concat(
+            vec![
+                kff(file(5, true, false, b"ACTGAC")),
+                kff(file(5, true, false, b"ACTGA")),
+            ],
+            &mut output,
+        )?;
+        assert!(!kff(output).header().uniq_kmer());
+
+        // A single input keep its flag
+        let mut output = Vec::new();
+        concat(vec![kff(file(5, true, false, b"ACTGAC"))], &mut output)?;
+        assert!(kff(output).header().uniq_kmer());
+
+        Ok(())
+    }
+
+    #[test]
+    fn not_compatible() -> error::Result<()> {
+        let mut output = Vec::new();
+
+        assert!(matches!(
+            concat(
+                vec![
+                    kff(file(5, true, false, b"ACTGAC")),
+                    kff(file(5, false, false, b"ACTGAC")),
+                ],
+                &mut output,
+            ),
+            Err(error::Error::Kff(error::Kff::HeaderNotCompatible))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,412 @@
+//! Edit header and footer of a KFF file in place
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::utils;
+
+use crate::section::values::AbcValues as _;
+
+/// Storage that can be truncated or extended, needed to replace a footer by a shorter one
+pub trait SetLen {
+    /// Truncate or extend storage to `size` bytes
+    fn set_len(&mut self, size: u64) -> std::io::Result<()>;
+}
+
+impl SetLen for std::fs::File {
+    fn set_len(&mut self, size: u64) -> std::io::Result<()> {
+        std::fs::File::set_len(self, size)
+    }
+}
+
+impl SetLen for std::io::Cursor<Vec<u8>> {
+    fn set_len(&mut self, size: u64) -> std::io::Result<()> {
+        self.get_mut().resize(size as usize, 0);
+
+        Ok(())
+    }
+}
+
+/// Patch header and footer of a KFF file without rewriting its sections
+///
+/// Header is rewritten in place, so its free block must keep the same size. Footer, the last
+/// Values section before final signature, is replaced or appended, `footer_size` is regenerated
+/// and final signature is rewritten after it. If footer contains a `crc32c` variable, file is
+/// checked against it before each change and its value is recomputed after, this requires to read
+/// all file. A file that doesn't match its checksum isn't modified.
+///
+/// ```no_run
+/// # fn main() -> kff::error::Result<()> {
+/// let mut editor = kff::tools::Editor::open("file.kff")?;
+///
+/// let mut header = editor.header().clone();
+/// header.set_canonical_kmer(true);
+/// editor.set_header(header)?;
+///
+/// let mut footer = editor.footer().cloned().unwrap_or_default();
+/// footer.insert("nb_kmer".to_string(), 42);
+/// editor.set_footer(footer)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Editor<F> {
+    inner: F,
+    header: section::Header,
+    footer: Option<section::Values>,
+
+    /// Position of footer or of final signature if there isn't footer
+    footer_position: u64,
+}
+
+impl<F> Editor<F>
+where
+    F: std::io::Read + std::io::Write + std::io::Seek + SetLen,
+{
+    /// Create an editor, file must start and end with `KFF`
+    pub fn new(mut inner: F) -> error::Result<Self> {
+        inner.seek(std::io::SeekFrom::Start(0))?;
+        let header = section::Header::read(&mut std::io::BufReader::new(&mut inner))?;
+
+        let end = inner.seek(std::io::SeekFrom::End(0))?;
+        if end < 3 {
+            return Err(error::Kff::MissingMagic("end".to_string()).into());
+        }
+        inner.seek(std::io::SeekFrom::Start(end - 3))?;
+        let mut signature = [0; 3];
+        inner.read_exact(&mut signature)?;
+        if &signature != b"KFF" {
+            return Err(error::Kff::MissingMagic("end".to_string()).into());
+        }
+
+        let (footer, footer_position) =
+            match crate::kff::find_footer(&mut inner, end - 3, &crate::ReadLimits::default())? {
+                Some((footer, position)) => (Some(footer), position),
+                None => (None, end - 3),
+            };
+
+        Ok(Self {
+            inner,
+            header,
+            footer,
+            footer_position,
+        })
+    }
+
+    /// Current header
+    pub fn header(&self) -> &section::Header {
+        &self.header
+    }
+
+    /// Current footer without `footer_size`, None if file hasn't footer
+    pub fn footer(&self) -> Option<&section::Values> {
+        self.footer.as_ref()
+    }
+
+    /// Rewrite header, its size must not change
+    ///
+    /// Error [ChecksumMismatch](error::Kff::ChecksumMismatch) if file doesn't match its checksum.
+    pub fn set_header(&mut self, header: section::Header) -> error::Result<&mut Self> {
+        let mut old = Vec::new();
+        self.header.write(&mut old)?;
+        let mut new = Vec::new();
+        header.write(&mut new)?;
+
+        if old.len() != new.len() {
+            return Err(error::Kff::HeaderSizeMismatch(old.len() as u64, new.len() as u64).into());
+        }
+        self.check_checksum()?;
+
+        self.inner.seek(std::io::SeekFrom::Start(0))?;
+        self.inner.write_all(&new)?;
+        self.header = header;
+
+        if self.has_checksum() {
+            self.write_footer()?;
+        }
+        self.inner.flush()?;
+
+        Ok(self)
+    }
+
+    /// Replace footer, or add it if file hasn't footer, `footer_size` is ignored and regenerated
+    ///
+    /// Error [ChecksumMismatch](error::Kff::ChecksumMismatch) if file doesn't match its checksum.
+    pub fn set_footer(&mut self, mut footer: section::Values) -> error::Result<&mut Self> {
+        self.check_checksum()?;
+
+        footer.remove("footer_size");
+        self.footer = Some(footer);
+
+        self.write_footer()?;
+        self.inner.flush()?;
+
+        Ok(self)
+    }
+
+    /// Consume editor to get inner storage
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+
+    fn has_checksum(&self) -> bool {
+        self.footer
+            .as_ref()
+            .map(|footer| footer.contains_key(utils::checksum::FOOTER_NAME))
+            .unwrap_or(false)
+    }
+
+    /// Check that checksum store in current footer, if any, match file
+    fn check_checksum(&mut self) -> error::Result<()> {
+        let expected = match self
+            .footer
+            .as_ref()
+            .and_then(|footer| footer.get(utils::checksum::FOOTER_NAME))
+        {
+            Some(expected) => *expected,
+            None => return Ok(()),
+        };
+
+        let actual = self.checksum()? as u64;
+        if expected != actual {
+            return Err(error::Kff::ChecksumMismatch(expected, actual).into());
+        }
+
+        Ok(())
+    }
+
+    /// Write footer, final signature and truncate after it
+    fn write_footer(&mut self) -> error::Result<()> {
+        if self.has_checksum() {
+            let checksum = self.checksum()?;
+            if let Some(footer) = self.footer.as_mut() {
+                footer.insert(utils::checksum::FOOTER_NAME.to_string(), checksum as u64);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        if let Some(footer) = self.footer.as_ref() {
+            bytes.push(b'v');
+            footer.write_as_footer(&mut bytes)?;
+        }
+        bytes.extend_from_slice(b"KFF");
+
+        self.inner
+            .seek(std::io::SeekFrom::Start(self.footer_position))?;
+        self.inner.write_all(&bytes)?;
+        self.inner
+            .set_len(self.footer_position + bytes.len() as u64)?;
+
+        Ok(())
+    }
+
+    /// Compute CRC32C of all bytes before footer
+    fn checksum(&mut self) -> error::Result<u32> {
+        self.inner.seek(std::io::SeekFrom::Start(0))?;
+
+        Ok(utils::checksum::crc32c_of(&mut self.inner, self.footer_position)?.value())
+    }
+}
+
+impl Editor<std::fs::File> {
+    /// Create an editor on file match with path, file is open in read and write mode
+    pub fn open<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        Self::new(
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Kff;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file(checksum: bool, index: bool) -> error::Result<Vec<u8>> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"producer: test".to_vec())?;
+        let mut writer = if checksum {
+            Kff::write_with_checksum(Vec::new(), header)?
+        } else {
+            Kff::write(Vec::new(), header)?
+        };
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 5);
+        values.insert("max".to_string(), 10);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone())?;
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[section::Block::new(
+                5,
+                1,
+                Kmer::from_ascii(b"ACTGAC", vec![3, 1], ENCODING),
+                0,
+            )],
+        )?;
+
+        if index {
+            writer.finalize_with_index()?;
+        } else {
+            writer.finalize()?;
+        }
+
+        Ok(writer.into_inner())
+    }
+
+    fn kmers(inner: Vec<u8>) -> error::Result<Vec<Kmer>> {
+        Kff::read(std::io::Cursor::new(inner))?.kmers().collect()
+    }
+
+    #[test]
+    fn header() -> error::Result<()> {
+        let input = file(false, false)?;
+        let mut editor = Editor::new(std::io::Cursor::new(input.clone()))?;
+        assert!(editor.footer().is_none());
+
+        let mut header = editor.header().clone();
+        header.set_canonical_kmer(true).set_uniq_kmer(false);
+        editor.set_header(header.clone())?;
+
+        header.set_free_block(b"producer: other".to_vec());
+        assert!(matches!(
+            editor.set_header(header),
+            Err(error::Error::Kff(error::Kff::HeaderSizeMismatch(26, 27)))
+        ));
+
+        let output = editor.into_inner().into_inner();
+        assert_eq!(output.len(), input.len());
+
+        let mut reader = Kff::read(std::io::Cursor::new(output.clone()))?;
+        assert!(reader.header().canonical_kmer());
+        assert!(!reader.header().uniq_kmer());
+        reader.validate()?;
+        assert_eq!(kmers(output)?, kmers(input)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn footer() -> error::Result<()> {
+        // Append a footer
+        let input = file(false, false)?;
+        let mut editor = Editor::new(std::io::Cursor::new(input.clone()))?;
+
+        let mut footer = section::Values::new();
+        footer.insert("nb_kmer".to_string(), 2);
+        editor.set_footer(footer.clone())?;
+
+        let output = editor.into_inner().into_inner();
+        Kff::read(std::io::Cursor::new(output.clone()))?.validate()?;
+        assert_eq!(kmers(output.clone())?, kmers(input)?);
+
+        // Replace it by a shorter one
+        let mut editor = Editor::new(std::io::Cursor::new(output))?;
+        assert_eq!(editor.footer(), Some(&footer));
+
+        let mut footer = section::Values::new();
+        footer.insert("n".to_string(), 3);
+        editor.set_footer(footer.clone())?;
+
+        let output = editor.into_inner().into_inner();
+        let mut reader = Kff::read(std::io::Cursor::new(output.clone()))?;
+        reader.validate()?;
+        assert_eq!(reader.values().get("n"), Some(&3));
+        assert_eq!(reader.values().get("nb_kmer"), None);
+        assert_eq!(
+            Editor::new(std::io::Cursor::new(output))?.footer(),
+            Some(&footer)
+        );
+
+        // Index is still reachable
+        let input = file(false, true)?;
+        let mut editor = Editor::new(std::io::Cursor::new(input))?;
+        let mut footer = editor.footer().cloned().unwrap();
+        footer.insert("nb_kmer".to_string(), 2);
+        editor.set_footer(footer)?;
+
+        let mut reader =
+            Kff::read_with_index(std::io::Cursor::new(editor.into_inner().into_inner()))?;
+        assert_eq!(reader.kmer_of_section(1)?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn checksum() -> error::Result<()> {
+        let input = file(true, true)?;
+        let mut editor = Editor::new(std::io::Cursor::new(input))?;
+
+        let mut header = editor.header().clone();
+        header.set_canonical_kmer(true);
+        editor.set_header(header)?;
+
+        let mut footer = editor.footer().cloned().unwrap();
+        footer.insert("nb_kmer".to_string(), 2);
+        editor.set_footer(footer)?;
+
+        let output = editor.into_inner().into_inner();
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+        reader.validate()?;
+        assert_eq!(reader.values().get("nb_kmer"), Some(&2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn corrupted() -> error::Result<()> {
+        let mut input = file(true, false)?;
+        // Flip a bit of last byte of Raw section, just before footer
+        let position = Editor::new(std::io::Cursor::new(input.clone()))?.footer_position;
+        input[position as usize - 1] ^= 1;
+
+        let mut editor = Editor::new(std::io::Cursor::new(input.clone()))?;
+        let expected = *editor.footer().unwrap().get("crc32c").unwrap();
+
+        let mut header = editor.header().clone();
+        header.set_canonical_kmer(true);
+        assert!(matches!(
+            editor.set_header(header),
+            Err(error::Error::Kff(error::Kff::ChecksumMismatch(e, _))) if e == expected
+        ));
+
+        let mut footer = editor.footer().cloned().unwrap();
+        footer.insert("nb_kmer".to_string(), 2);
+        assert!(matches!(
+            editor.set_footer(footer),
+            Err(error::Error::Kff(error::Kff::ChecksumMismatch(e, _))) if e == expected
+        ));
+
+        assert!(!editor.header().canonical_kmer());
+        assert_eq!(editor.footer().unwrap().get("nb_kmer"), None);
+        assert_eq!(editor.into_inner().into_inner(), input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn not_kff() -> error::Result<()> {
+        let mut input = file(false, false)?;
+        input.pop();
+
+        assert!(matches!(
+            Editor::new(std::io::Cursor::new(input)),
+            Err(error::Error::Kff(error::Kff::MissingMagic(_)))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+//! Export KFF file as FASTA sequences
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::Kff;
+use crate::KffWrite as _;
+
+/// What is written as a FASTA record
+#[derive(
+    std::clone::Clone, std::marker::Copy, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum Mode {
+    /// Each block is a record
+    Blocks,
+
+    /// Kmers are assembled in maximal unitigs, each unitig is a record
+    Unitigs,
+}
+
+/// Write kmers of `input` in `outer` in FASTA format, return number of record write
+///
+/// Header line of each record contains record number, number of kmer and data of each kmer,
+/// separate by comma, formatted like [to_text](super::to_text). In [Mode::Blocks] header of
+/// blocks of Minimizer sections contains minimizer and minimizer offset. In [Mode::Unitigs] all
+/// kmers are kept in memory, if file is canonical a kmer and its reverse complement are the same
+/// node, unitigs never contain a kmer twice.
+pub fn to_fasta<R, W>(mut input: Kff<R>, mut outer: W, mode: Mode) -> error::Result<u64>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+    W: std::io::Write,
+{
+    let encoding = *input.header().encoding();
+
+    let nb_record = match mode {
+        Mode::Blocks => {
+            let mut nb_record = 0;
+
+            while let Some(section) = input.next_block_section() {
+                let section = section?;
+                let minimizer = section
+                    .minimizer()
+                    .map(|m| crate::kmer::bits2seq(m, encoding));
+
+                for block in section.into_blocks() {
+                    let nb_kmer = block.nb_kmer();
+                    let data: Vec<&[u8]> = match block.data_size() {
+                        0 => Vec::new(),
+                        data_size => block.kmer().data().chunks(*data_size).collect(),
+                    };
+
+                    write!(outer, ">{} nb_kmer={}", nb_record, nb_kmer)?;
+                    if let Some(minimizer) = minimizer.as_ref() {
+                        write!(
+                            outer,
+                            " minimizer={} offset={}",
+                            String::from_utf8_lossy(minimizer),
+                            block.minimizer_offset()
+                        )?;
+                    }
+                    write_data(&mut outer, &data)?;
+
+                    outer.write_bytes(&block.kmer().seq(encoding))?;
+                    outer.write_bytes(b"\n")?;
+
+                    nb_record += 1;
+                }
+            }
+
+            nb_record
+        }
+        Mode::Unitigs => {
+            let canonical = *input.header().canonical_kmer();
+            let mut graph = Graph::new(canonical.then_some(encoding));
+
+            while let Some(section) = input.next_kmer_section() {
+                for kmer in section? {
+                    graph.add(kmer.seq(encoding), kmer.data().clone());
+                }
+            }
+
+            let mut nb_record = 0;
+            for (seq, data) in graph.unitigs() {
+                write!(outer, ">{} nb_kmer={}", nb_record, data.len())?;
+                let data: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+                write_data(&mut outer, &data)?;
+
+                outer.write_bytes(&seq)?;
+                outer.write_bytes(b"\n")?;
+
+                nb_record += 1;
+            }
+
+            nb_record
+        }
+    };
+
+    outer.flush()?;
+
+    Ok(nb_record)
+}
+
+/// Write data of kmers and end of header line, data are written only if all kmers have data
+fn write_data<W>(outer: &mut W, data: &[&[u8]]) -> error::Result<()>
+where
+    W: std::io::Write,
+{
+    if !data.is_empty() && data.iter().all(|d| !d.is_empty()) {
+        let data: Vec<String> = data.iter().map(|d| super::text::data2string(d)).collect();
+
+        write!(outer, " data={}", data.join(","))?;
+    }
+
+    outer.write_bytes(b"\n")
+}
+
+/// Node centric de Bruijn graph of kmers
+struct Graph {
+    /// Encoding of file if it's canonical, to compute reverse complement of kmers
+    rev_comp: Option<u8>,
+    kmers: rustc_hash::FxHashMap<Vec<u8>, usize>,
+    nodes: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Graph {
+    fn new(rev_comp: Option<u8>) -> Self {
+        Self {
+            rev_comp,
+            kmers: rustc_hash::FxHashMap::default(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Add a kmer, if kmer is already present it's ignored
+    fn add(&mut self, seq: Vec<u8>, data: Vec<u8>) {
+        if self.find(&seq).is_none() {
+            self.kmers.insert(seq.clone(), self.nodes.len());
+            self.nodes.push((seq, data));
+        }
+    }
+
+    /// Get id of node of this kmer
+    fn find(&self, seq: &[u8]) -> Option<usize> {
+        match self.kmers.get(seq) {
+            Some(id) => Some(*id),
+            None => self.rev_comp.and_then(|encoding| {
+                let reverse =
+                    crate::kmer::rev_comp(&crate::kmer::seq2bits(seq, encoding), encoding);
+
+                self.kmers
+                    .get(&crate::kmer::bits2seq(&reverse, encoding))
+                    .cloned()
+            }),
+        }
+    }
+
+    /// Get id and sequence of all successors of kmer
+    fn successors(&self, seq: &[u8]) -> Vec<(usize, Vec<u8>)> {
+        b"ACGT"
+            .iter()
+            .filter_map(|nuc| {
+                let mut next = seq[1..].to_vec();
+                next.push(*nuc);
+
+                self.find(&next).map(|id| (id, next))
+            })
+            .collect()
+    }
+
+    /// Get id and sequence of all predecessors of kmer
+    fn predecessors(&self, seq: &[u8]) -> Vec<(usize, Vec<u8>)> {
+        b"ACGT"
+            .iter()
+            .filter_map(|nuc| {
+                let mut prev = vec![*nuc];
+                prev.extend_from_slice(&seq[..seq.len() - 1]);
+
+                self.find(&prev).map(|id| (id, prev))
+            })
+            .collect()
+    }
+
+    /// Extend `seq` on right, or on left if `right` is false, until a branch, an already used node or a dead end
+    fn extend(&self, seq: &[u8], right: bool, used: &mut [bool]) -> Vec<(usize, Vec<u8>)> {
+        let mut path = Vec::new();
+        let mut current = seq.to_vec();
+
+        loop {
+            let mut neighbors = if right {
+                self.successors(&current)
+            } else {
+                self.predecessors(&current)
+            };
+            if neighbors.len() != 1 {
+                break;
+            }
+
+            let (id, next) = neighbors.pop().unwrap();
+            let back = if right {
+                self.predecessors(&next)
+            } else {
+                self.successors(&next)
+            };
+            if used[id] || back.len() != 1 {
+                break;
+            }
+
+            used[id] = true;
+            current = next.clone();
+            path.push((id, next));
+        }
+
+        path
+    }
+
+    /// Build all maximal unitigs, in order of first kmer
+    fn unitigs(&self) -> Vec<(Vec<u8>, Vec<Vec<u8>>)> {
+        let mut used = vec![false; self.nodes.len()];
+        let mut unitigs = Vec::new();
+
+        for (id, (seq, _)) in self.nodes.iter().enumerate() {
+            if used[id] {
+                continue;
+            }
+            used[id] = true;
+
+            let right = self.extend(seq, true, &mut used);
+            let left = self.extend(seq, false, &mut used);
+
+            let mut unitig = Vec::new();
+            let mut datas = Vec::new();
+            for (id, kmer) in left.iter().rev().chain(std::iter::once(&(id, seq.clone()))) {
+                if unitig.is_empty() {
+                    unitig = kmer.clone();
+                } else {
+                    unitig.push(kmer[kmer.len() - 1]);
+                }
+
+                datas.push(self.nodes[*id].1.clone());
+            }
+
+            for (id, kmer) in right {
+                unitig.push(kmer[kmer.len() - 1]);
+                datas.push(self.nodes[id].1.clone());
+            }
+
+            unitigs.push((unitig, datas));
+        }
+
+        unitigs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section;
+    use crate::section::values::AbcValues as _;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file(canonical: bool, kmers: &[&[u8]]) -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 3);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 255);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone()).unwrap();
+
+        let blocks: Vec<section::Block> = kmers
+            .iter()
+            .enumerate()
+            .map(|(i, seq)| {
+                section::Block::new(
+                    3,
+                    1,
+                    Kmer::from_ascii(seq, vec![i as u8; seq.len() - 2], ENCODING),
+                    0,
+                )
+            })
+            .collect();
+
+        // Last kmer is in a Minimizer section, minimizer is its prefix
+        let (last, others) = blocks.split_last().unwrap();
+        writer
+            .write_raw(section::Raw::new(&values).unwrap(), others)
+            .unwrap();
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values).unwrap(),
+                crate::kmer::seq2bits(&kmers[kmers.len() - 1][..2], ENCODING),
+                std::slice::from_ref(last),
+            )
+            .unwrap();
+        writer.finalize().unwrap();
+
+        writer.into_inner()
+    }
+
+    fn fasta(input: Vec<u8>, mode: Mode) -> error::Result<String> {
+        let mut output = Vec::new();
+        to_fasta(Kff::read(std::io::Cursor::new(input))?, &mut output, mode)?;
+
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn blocks() -> error::Result<()> {
+        let input = file(false, &[b"ACTGA", b"CCT"]);
+
+        assert_eq!(
+            fasta(input, Mode::Blocks)?,
+            ">0 nb_kmer=3 data=0,0,0\nACTGA\n>1 nb_kmer=1 minimizer=CC offset=0 data=1\nCCT\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unitigs() -> error::Result<()> {
+        // ACT CTG TGA GAA form a path, CCT branch on CTG
+        let input = file(false, &[b"ACTGA", b"GAA", b"CCT"]);
+
+        assert_eq!(
+            fasta(input, Mode::Unitigs)?,
+            ">0 nb_kmer=1 data=0\nACT\n>1 nb_kmer=3 data=0,0,1\nCTGAA\n>2 nb_kmer=1 data=2\nCCT\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_unitigs() -> error::Result<()> {
+        // GTC is reverse complement of GAC, so ACTGAC is a unitig
+        let input = file(true, &[b"ACTGA", b"GTC"]);
+
+        assert_eq!(
+            fasta(input, Mode::Unitigs)?,
+            ">0 nb_kmer=4 data=0,0,0,1\nACTGAC\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+//! Filter and transform kmers of a KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::section::values::AbcValues as _;
+use crate::Kff;
+use crate::Kmer;
+use crate::Seq2Bit;
+
+/// A step of a [KffFilter]
+enum Step {
+    Filter(Box<dyn Fn(&Kmer) -> bool>),
+    Map(Box<dyn Fn(Kmer) -> Kmer>),
+}
+
+/// Pipeline of predicates and mappers apply on each kmer, and its data, of a KFF file
+///
+/// Steps are applied in order they are added, a kmer rejected by a predicate isn't seen by next
+/// steps.
+///
+/// ```
+/// let filter = kff::tools::KffFilter::new()
+///     .min_count(2)
+///     .min_entropy(1.0)
+///     .map(|mut kmer| {
+///         kmer.data_mut().iter_mut().for_each(|x| *x = x.saturating_sub(1));
+///         kmer
+///     });
+/// ```
+#[derive(std::default::Default)]
+pub struct KffFilter {
+    steps: Vec<Step>,
+}
+
+impl KffFilter {
+    /// Create an empty pipeline, all kmers are kept unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only kmers where `predicate` is true
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Kmer) -> bool + 'static,
+    {
+        self.steps.push(Step::Filter(Box::new(predicate)));
+        self
+    }
+
+    /// Replace each kmer by result of `mapper`, `mapper` must keep kmer size
+    pub fn map<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(Kmer) -> Kmer + 'static,
+    {
+        self.steps.push(Step::Map(Box::new(mapper)));
+        self
+    }
+
+    /// Keep only kmers with a count, data read as big endian unsigned integer, greater or equal to `min`
+    pub fn min_count(self, min: u64) -> Self {
+        self.filter(move |kmer| count(kmer.data()) >= min)
+    }
+
+    /// Keep only kmers with a Shannon entropy of nucleotides, between 0 and 2 bits, greater or equal to `min`
+    pub fn min_entropy(self, min: f64) -> Self {
+        self.filter(move |kmer| entropy(kmer.seq2bit()) >= min)
+    }
+
+    /// Apply steps on one kmer, None if kmer is rejected
+    pub fn apply(&self, mut kmer: Kmer) -> Option<Kmer> {
+        for step in self.steps.iter() {
+            match step {
+                Step::Filter(predicate) => {
+                    if !predicate(&kmer) {
+                        return None;
+                    }
+                }
+                Step::Map(mapper) => kmer = mapper(kmer),
+            }
+        }
+
+        Some(kmer)
+    }
+
+    /// Apply steps on each kmer of `input` and write kept kmers in `outer`, return number of kmer write
+    ///
+    /// Input is read section by section. Kept kmers of a section are merged in blocks of at most
+    /// `max` kmers and written in a section of the same type with the same values, `ordered` is
+    /// set to false and `data_size` follow size of data of kept kmers. In a Minimizer section a
+    /// kmer that no longer contains minimizer is written in a Raw section after it. An index and
+    /// a footer are written at end.
+    pub fn run<R, W>(&self, mut input: Kff<R>, outer: W) -> error::Result<u64>
+    where
+        R: std::io::Read + std::io::BufRead + crate::KffRead,
+        W: std::io::Write + crate::KffWrite,
+    {
+        let mut output = Kff::write(outer, input.header().clone())?;
+        let mut nb_kmer = 0;
+
+        while let Some(section) = input.next_block_section() {
+            let section = section?;
+
+            let k = input.values().get_value("k")?;
+            let max = input.values().get_value("max")?;
+
+            let mut kept = Vec::new();
+            let mut data_size = None;
+            for kmer in section.blocks().iter().cloned().flatten() {
+                if let Some(kmer) = self.apply(kmer) {
+                    if kmer.seq2bit().len() as u64 != k * 2 {
+                        return Err(error::Kff::KmerSizeMismatch(
+                            k,
+                            kmer.seq2bit().len() as u64 / 2,
+                        )
+                        .into());
+                    }
+
+                    let size = *data_size.get_or_insert(kmer.data().len());
+                    if kmer.data().len() != size {
+                        return Err(error::Kff::DataSizeMismatch(size, kmer.data().len()).into());
+                    }
+
+                    kept.push(kmer);
+                }
+            }
+
+            if kept.is_empty() {
+                continue;
+            }
+            nb_kmer += kept.len() as u64;
+
+            let mut values = input.values().clone();
+            values.insert("data_size".to_string(), data_size.unwrap_or(0) as u64);
+            if values.contains_key("ordered") {
+                values.insert("ordered".to_string(), false as u64);
+            }
+            if output.values() != &values {
+                output.write_values(values.clone())?;
+            }
+
+            let raw = match section.minimizer() {
+                None => kept,
+                Some(minimizer) => {
+                    let mut raw = Vec::new();
+                    let mut blocks = Vec::new();
+                    for kmer in kept {
+                        match section::Block::new_with_minimizer(
+                            k,
+                            kmer.data().len(),
+                            kmer.clone(),
+                            minimizer,
+                        ) {
+                            Ok(block) => blocks.push(block),
+                            Err(_) => raw.push(kmer),
+                        }
+                    }
+
+                    if !blocks.is_empty() {
+                        output.write_minimizer(
+                            section::Minimizer::new(&values)?,
+                            minimizer.clone(),
+                            &super::compact::super_kmers(blocks, k, max, Some(minimizer), None),
+                        )?;
+                    }
+
+                    raw
+                }
+            };
+
+            if !raw.is_empty() {
+                let blocks = raw
+                    .into_iter()
+                    .map(|kmer| section::Block::new(k, kmer.data().len(), kmer, 0))
+                    .collect();
+                output.write_raw(
+                    section::Raw::new(&values)?,
+                    &super::compact::super_kmers(blocks, k, max, None, None),
+                )?;
+            }
+        }
+
+        output.finalize_with_index()?;
+
+        Ok(nb_kmer)
+    }
+}
+
+/// Read data as a big endian unsigned integer, saturate to [u64::MAX]
+pub fn count(data: &[u8]) -> u64 {
+    data.iter().fold(0u64, |acc, x| {
+        acc.checked_mul(256)
+            .map(|acc| acc | *x as u64)
+            .unwrap_or(u64::MAX)
+    })
+}
+
+/// Shannon entropy, in bits, of nucleotides of a kmer
+pub fn entropy(kmer: &Seq2Bit) -> f64 {
+    let mut counts = [0u64; 4];
+    for nuc in kmer.chunks_exact(2) {
+        counts[((nuc[0] as usize) << 1) | nuc[1] as usize] += 1;
+    }
+
+    let total = (kmer.len() / 2) as f64;
+    counts
+        .iter()
+        .filter(|count| **count != 0)
+        .map(|count| {
+            let p = *count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file() -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 10);
+        values.insert("data_size".to_string(), 1);
+        values.insert("ordered".to_string(), true as u64);
+        writer.write_values(values.clone()).unwrap();
+
+        // ACTGAC contains ACTGA (3) and CTGAC (1), AAAAA is low complexity
+        writer
+            .write_raw(
+                section::Raw::new(&values).unwrap(),
+                &[
+                    section::Block::new(5, 1, Kmer::from_ascii(b"ACTGAC", vec![3, 1], ENCODING), 0),
+                    section::Block::new(5, 1, Kmer::from_ascii(b"AAAAA", vec![5], ENCODING), 0),
+                ],
+            )
+            .unwrap();
+
+        // Minimizer TG at offset 2 in first kmer
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values).unwrap(),
+                crate::kmer::seq2bits(b"TG", ENCODING),
+                &[section::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(b"GCTGAT", vec![2, 4], ENCODING),
+                    2,
+                )],
+            )
+            .unwrap();
+        writer.finalize_with_index().unwrap();
+
+        writer.into_inner()
+    }
+
+    /// Minimizer and kmers with data of each kmer section
+    type Sections = Vec<(Option<Vec<u8>>, Vec<(Vec<u8>, Vec<u8>)>)>;
+
+    fn sections(kff: Vec<u8>) -> error::Result<Sections> {
+        let mut reader = Kff::read(std::io::Cursor::new(kff))?;
+
+        let mut sections = Vec::new();
+        while let Some(section) = reader.next_block_section() {
+            let section = section?;
+            let minimizer = section
+                .minimizer()
+                .map(|m| crate::kmer::bits2seq(m, ENCODING));
+            let kmers = section
+                .into_blocks()
+                .into_iter()
+                .flatten()
+                .map(|kmer| (kmer.seq(ENCODING), kmer.data().clone()))
+                .collect();
+
+            sections.push((minimizer, kmers));
+        }
+
+        Ok(sections)
+    }
+
+    #[test]
+    fn count_entropy() {
+        assert_eq!(count(&[]), 0);
+        assert_eq!(count(&[1, 2]), 258);
+        assert_eq!(count(&[1; 9]), u64::MAX);
+
+        assert_eq!(entropy(&crate::kmer::seq2bits(b"AAAAA", ENCODING)), 0.0);
+        assert_eq!(entropy(&crate::kmer::seq2bits(b"ACGT", ENCODING)), 2.0);
+        assert_eq!(entropy(&crate::kmer::seq2bits(b"AACC", ENCODING)), 1.0);
+    }
+
+    #[test]
+    fn identity() -> error::Result<()> {
+        let mut output = Vec::new();
+        assert_eq!(
+            KffFilter::new().run(Kff::read(std::io::Cursor::new(file()))?, &mut output)?,
+            5
+        );
+
+        assert_eq!(sections(output)?, sections(file())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn filter() -> error::Result<()> {
+        let filter = KffFilter::new().min_count(2).min_entropy(1.0);
+
+        let mut output = Vec::new();
+        assert_eq!(
+            filter.run(Kff::read(std::io::Cursor::new(file()))?, &mut output)?,
+            3
+        );
+
+        assert_eq!(
+            sections(output.clone())?,
+            vec![
+                (None, vec![(b"ACTGA".to_vec(), vec![3])]),
+                (
+                    Some(b"TG".to_vec()),
+                    vec![(b"GCTGA".to_vec(), vec![2]), (b"CTGAT".to_vec(), vec![4])]
+                ),
+            ]
+        );
+
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(output))?;
+        reader.next_block_section().unwrap()?;
+        assert_eq!(reader.values().get("ordered"), Some(&0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn map() -> error::Result<()> {
+        // Data become 2 bytes, GCTGA lose minimizer TG
+        let filter = KffFilter::new()
+            .map(|mut kmer| {
+                let data = vec![0, kmer.data()[0]];
+                kmer.set_data(data);
+                kmer
+            })
+            .map(|kmer| {
+                if kmer.seq(ENCODING) == b"GCTGA" {
+                    Kmer::from_ascii(b"GCCCA", kmer.data().clone(), ENCODING)
+                } else {
+                    kmer
+                }
+            });
+
+        let mut output = Vec::new();
+        filter.run(Kff::read(std::io::Cursor::new(file()))?, &mut output)?;
+
+        let sections = sections(output)?;
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[1].0, Some(b"TG".to_vec()));
+        assert_eq!(sections[1].1, vec![(b"CTGAT".to_vec(), vec![0, 4])]);
+        assert_eq!(sections[2], (None, vec![(b"GCCCA".to_vec(), vec![0, 2])]));
+
+        let resize = KffFilter::new().map(|_| Kmer::from_ascii(b"ACT", vec![], ENCODING));
+        assert!(matches!(
+            resize.run(Kff::read(std::io::Cursor::new(file()))?, Vec::new()),
+            Err(error::Error::Kff(error::Kff::KmerSizeMismatch(5, 3)))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,852 @@
+//! Count kmers of FASTA or FASTQ reads and write them in a KFF file
+
+/* std use */
+use std::io::Write as _;
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::Kff;
+use crate::KffRead as _;
+use crate::Kmer;
+use crate::Seq2Bit;
+
+use crate::section::values::AbcValues as _;
+
+/// Where kmer counts are stored during counting
+#[derive(std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq)]
+pub enum Storage {
+    /// Each distinct kmer is stored in a hash map
+    HashMap,
+
+    /// Kmers are buffered as fixed size records, each time `buffer` kmers are buffered they are
+    /// sorted and written in a temporary file in `directory`, at end temporary files are merged by
+    /// groups of at most [MAX_RUNS_MERGED] files until all can be merged at once
+    Sorted {
+        /// Number of kmers kept in memory
+        buffer: usize,
+
+        /// Directory where temporary files are written
+        directory: std::path::PathBuf,
+    },
+}
+
+/// Maximal number of temporary files open at same time by [Storage::Sorted]
+pub const MAX_RUNS_MERGED: usize = 64;
+
+/// Parameters of kmer counting
+#[derive(
+    getset::Getters,
+    getset::Setters,
+    std::clone::Clone,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Parameters {
+    /// Size of kmer
+    k: u64,
+
+    /// Size of minimizer, if set kmers are written in Minimizer sections
+    m: Option<u64>,
+
+    /// Maximal number of kmer per block
+    max: u64,
+
+    /// Number of bytes used to store count, counts are saturated
+    data_size: usize,
+
+    /// Storage used during counting
+    storage: Storage,
+}
+
+impl Parameters {
+    /// Create parameters for kmer of size `k`, without minimizer, at most 255 kmers per block,
+    /// counts stored on 1 byte and a hash map storage
+    pub fn new(k: u64) -> Self {
+        Self {
+            k,
+            m: None,
+            max: 255,
+            data_size: 1,
+            storage: Storage::HashMap,
+        }
+    }
+}
+
+/// Count kmers of each record of `input`, a FASTA or FASTQ file, and write them in `outer`, return number of distinct kmer
+///
+/// Any character other than A, C, G or T (case insensitive) break kmers. If canonical flag of
+/// `header` is set, kmers are replaced by minimum of kmer and its reverse complement in 2 bits
+/// representation. Kmers are written sorted and merged in blocks of at most `max` kmers, without
+/// minimizer in Raw sections, with minimizer one Minimizer section is written per minimizer,
+/// minimizer is smallest m-mer of kmer. With minimizer kmers are sorted by minimizer then by kmer,
+/// so only kmers of one minimizer are kept in memory during writing.
+pub fn from_reads<R, W>(
+    input: R,
+    outer: W,
+    header: section::Header,
+    parameters: &Parameters,
+) -> error::Result<u64>
+where
+    R: std::io::BufRead,
+    W: std::io::Write + crate::KffWrite,
+{
+    let k = parameters.k;
+    if k == 0 {
+        return Err(error::Kff::KmerSizeIsZero.into());
+    }
+    if let Some(m) = parameters.m {
+        if m > k {
+            return Err(error::Kff::MinimizerSizeTooLarge(m, k).into());
+        }
+    }
+
+    let encoding = *header.encoding();
+    let canonical = *header.canonical_kmer();
+
+    // Record of a kmer is its minimizer, if any, then kmer each packed on whole bytes, order of
+    // records is order of minimizers then of kmers
+    let key_len = crate::bytes2store_k(k) as usize;
+    let prefix_len = parameters
+        .m
+        .map(|m| crate::bytes2store_k(m) as usize)
+        .unwrap_or(0);
+    let record_len = prefix_len + key_len;
+
+    let mut counts = match &parameters.storage {
+        Storage::HashMap => Counts::Hash(rustc_hash::FxHashMap::default()),
+        Storage::Sorted { buffer, directory } => Counts::Sorted(SortedRuns::new(
+            *buffer,
+            directory.clone(),
+            record_len,
+            MAX_RUNS_MERGED,
+        )),
+    };
+
+    let mut record = vec![0; record_len];
+    let mut reverse = vec![0; key_len];
+    for read in Records::new(input) {
+        let read = read?;
+
+        for run in read.split(|nuc| !b"ACGTacgt".contains(nuc)) {
+            if run.len() < k as usize {
+                continue;
+            }
+
+            for window in run.windows(k as usize) {
+                let (prefix, key) = record.split_at_mut(prefix_len);
+
+                pack(window.iter(), encoding, false, key);
+                if canonical {
+                    pack(window.iter().rev(), encoding, true, &mut reverse);
+                    if reverse[..] < key[..] {
+                        key.copy_from_slice(&reverse);
+                    }
+                }
+
+                if let Some(m) = parameters.m {
+                    let m = m as usize;
+                    let kmer = &bitvec::slice::BitSlice::<u8, bitvec::order::Msb0>::from_slice(key)
+                        [..k as usize * 2];
+                    let offset = crate::kmer::minimizer_offset(kmer, m)?;
+
+                    prefix.fill(0);
+                    bitvec::slice::BitSlice::<u8, bitvec::order::Msb0>::from_slice_mut(prefix)
+                        [..m * 2]
+                        .copy_from_bitslice(&kmer[offset * 2..(offset + m) * 2]);
+                }
+
+                counts.add(&record)?;
+            }
+        }
+    }
+
+    let mut values = section::Values::new();
+    values.insert("k".to_string(), k);
+    values.insert("max".to_string(), parameters.max);
+    values.insert("data_size".to_string(), parameters.data_size as u64);
+    values.insert("ordered".to_string(), (parameters.max == 1) as u64);
+    if let Some(m) = parameters.m {
+        values.insert("m".to_string(), m);
+    }
+
+    let mut output = Kff::write(outer, header)?;
+    output.write_values(values.clone())?;
+
+    let max_count = match parameters.data_size {
+        0..=7 => (1 << (parameters.data_size * 8)) - 1,
+        _ => u64::MAX,
+    };
+
+    let mut nb_kmer = 0;
+    let mut blocks = Vec::new();
+    let mut minimizer = None;
+
+    for entry in counts.into_sorted()? {
+        let (record, count) = entry?;
+        nb_kmer += 1;
+
+        let mut seq =
+            bitvec::vec::BitVec::<u8, bitvec::order::Msb0>::from_vec(record[prefix_len..].to_vec());
+        seq.truncate(k as usize * 2);
+
+        let count = std::cmp::min(count, max_count).to_be_bytes();
+        let mut data = vec![0; parameters.data_size.saturating_sub(8)];
+        data.extend_from_slice(&count[8usize.saturating_sub(parameters.data_size)..]);
+
+        match parameters.m {
+            None => {
+                blocks.push(section::Block::new(
+                    k,
+                    parameters.data_size,
+                    Kmer::new(seq.into_boxed_bitslice(), data),
+                    0,
+                ));
+
+                if blocks.len() == super::text::KMER_PER_SECTION {
+                    write_raw(&mut output, &values, std::mem::take(&mut blocks))?;
+                }
+            }
+            Some(m) => {
+                let (offset, kmer_minimizer) = crate::kmer::minimizer(&seq, m as usize)?;
+
+                // Records are sorted by minimizer, all kmers of previous minimizer are read
+                if minimizer.as_ref() != Some(&kmer_minimizer) {
+                    if let Some(previous) = minimizer.replace(kmer_minimizer) {
+                        write_minimizer(
+                            &mut output,
+                            &values,
+                            previous,
+                            std::mem::take(&mut blocks),
+                        )?;
+                    }
+                }
+
+                blocks.push(section::Block::new(
+                    k,
+                    parameters.data_size,
+                    Kmer::new(seq.into_boxed_bitslice(), data),
+                    offset,
+                ));
+            }
+        }
+    }
+
+    match minimizer {
+        Some(minimizer) => write_minimizer(&mut output, &values, minimizer, blocks)?,
+        None if !blocks.is_empty() => write_raw(&mut output, &values, blocks)?,
+        None => (),
+    }
+
+    output.finalize_with_index()?;
+
+    Ok(nb_kmer)
+}
+
+/// Iterator on sequences of a FASTA or FASTQ file
+///
+/// FASTA records could be on many lines, FASTQ records must be on four lines.
+pub struct Records<R>
+where
+    R: std::io::BufRead,
+{
+    inner: R,
+    line: Vec<u8>,
+    nb_record: u64,
+}
+
+impl<R> Records<R>
+where
+    R: std::io::BufRead,
+{
+    /// Create a new records iterator
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+            nb_record: 0,
+        }
+    }
+
+    /// Read next line without end of line in `self.line`, return false at end of file
+    fn read_line(&mut self) -> error::Result<bool> {
+        self.line.clear();
+        if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+
+        while matches!(self.line.last(), Some(b'\n' | b'\r')) {
+            self.line.pop();
+        }
+
+        Ok(true)
+    }
+
+    fn next_record(&mut self) -> error::Result<Option<Vec<u8>>> {
+        // Line could already contain header of this record
+        while self.line.is_empty() {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+        }
+        self.nb_record += 1;
+
+        match self.line[0] {
+            b'>' => {
+                let mut seq = Vec::new();
+                while self.read_line()? {
+                    if self.line.first() == Some(&b'>') {
+                        return Ok(Some(seq));
+                    }
+                    seq.extend_from_slice(&self.line);
+                }
+                self.line.clear();
+
+                Ok(Some(seq))
+            }
+            b'@' => {
+                if !self.read_line()? {
+                    return Err(self.error("sequence is missing"));
+                }
+                let seq = std::mem::take(&mut self.line);
+
+                if !self.read_line()? || self.line.first() != Some(&b'+') {
+                    return Err(self.error("separator '+' is missing"));
+                }
+
+                if !self.read_line()? || self.line.len() != seq.len() {
+                    return Err(self.error("quality and sequence length are different"));
+                }
+                self.line.clear();
+
+                Ok(Some(seq))
+            }
+            c => {
+                self.line.clear();
+                Err(self.error(&format!("record begin with '{}'", c as char)))
+            }
+        }
+    }
+
+    fn error(&self, msg: &str) -> error::Error {
+        error::Kff::BadSequenceRecord(self.nb_record, msg.to_string()).into()
+    }
+}
+
+impl<R> std::iter::Iterator for Records<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = error::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Pack nucleotides in 2 bits with `encoding` in `packed`, complement nucleotides if `complement` is true
+fn pack<'a, I>(seq: I, encoding: u8, complement: bool, packed: &mut [u8])
+where
+    I: std::iter::Iterator<Item = &'a u8>,
+{
+    packed.fill(0);
+
+    for (i, nuc) in seq.enumerate() {
+        // A: 0, C: 1, T: 2, G: 3 complement switch second bit
+        let internal = ((nuc >> 1) & 0b11) ^ ((complement as u8) << 1);
+        let bits = (encoding >> (6 - internal * 2)) & 0b11;
+
+        packed[i / 4] |= bits << (6 - (i % 4) * 2);
+    }
+}
+
+fn write_raw<W>(
+    output: &mut Kff<W>,
+    values: &section::Values,
+    blocks: Vec<section::Block>,
+) -> error::Result<()>
+where
+    W: std::io::Write + crate::KffWrite,
+{
+    let k = values["k"];
+    let max = values["max"];
+
+    let blocks = super::compact::super_kmers(blocks, k, max, None, None);
+    output.write_raw(section::Raw::new(values)?, &blocks)
+}
+
+fn write_minimizer<W>(
+    output: &mut Kff<W>,
+    values: &section::Values,
+    minimizer: Seq2Bit,
+    blocks: Vec<section::Block>,
+) -> error::Result<()>
+where
+    W: std::io::Write + crate::KffWrite,
+{
+    let k = values["k"];
+    let max = values["max"];
+
+    let blocks = super::compact::super_kmers(blocks, k, max, Some(&minimizer), None);
+    output.write_minimizer(section::Minimizer::new(values)?, minimizer, &blocks)
+}
+
+type SortedCounts = Box<dyn std::iter::Iterator<Item = error::Result<(Vec<u8>, u64)>>>;
+
+enum Counts {
+    Hash(rustc_hash::FxHashMap<Vec<u8>, u64>),
+    Sorted(SortedRuns),
+}
+
+impl Counts {
+    fn add(&mut self, record: &[u8]) -> error::Result<()> {
+        match self {
+            Counts::Hash(map) => {
+                match map.get_mut(record) {
+                    Some(count) => *count = count.saturating_add(1),
+                    None => {
+                        map.insert(record.to_vec(), 1);
+                    }
+                }
+
+                Ok(())
+            }
+            Counts::Sorted(runs) => runs.add(record),
+        }
+    }
+
+    fn into_sorted(self) -> error::Result<SortedCounts> {
+        match self {
+            Counts::Hash(map) => {
+                let mut counts: Vec<(Vec<u8>, u64)> = map.into_iter().collect();
+                counts.sort_unstable();
+
+                Ok(Box::new(counts.into_iter().map(Ok)))
+            }
+            Counts::Sorted(runs) => Ok(Box::new(runs.merge()?)),
+        }
+    }
+}
+
+static NB_RUN: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Records buffered in memory and sorted runs of counts write in temporary files
+struct SortedRuns {
+    /// Records of `record_len` bytes one after the other
+    buffer: Vec<u8>,
+    capacity: usize,
+    directory: std::path::PathBuf,
+    record_len: usize,
+    fan_in: usize,
+    runs: Vec<std::path::PathBuf>,
+}
+
+impl SortedRuns {
+    fn new(
+        capacity: usize,
+        directory: std::path::PathBuf,
+        record_len: usize,
+        fan_in: usize,
+    ) -> Self {
+        let capacity = std::cmp::max(capacity, 1);
+
+        Self {
+            buffer: Vec::with_capacity(capacity * record_len),
+            capacity,
+            directory,
+            record_len,
+            fan_in: std::cmp::max(fan_in, 2),
+            runs: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, record: &[u8]) -> error::Result<()> {
+        self.buffer.extend_from_slice(record);
+
+        if self.buffer.len() >= self.capacity * self.record_len {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a new temporary file, it's removed when SortedRuns or Merger is dropped
+    fn create_run(&mut self) -> error::Result<std::io::BufWriter<std::fs::File>> {
+        let path = self.directory.join(format!(
+            "kff_from_reads_{}_{}.tmp",
+            std::process::id(),
+            NB_RUN.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        self.runs.push(path.clone());
+
+        Ok(std::io::BufWriter::new(std::fs::File::create(&path)?))
+    }
+
+    /// Sort buffer, count kmers and write them in a new temporary file
+    fn spill(&mut self) -> error::Result<()> {
+        let mut output = self.create_run()?;
+
+        let mut records: Vec<&[u8]> = self.buffer.chunks_exact(self.record_len).collect();
+        records.sort_unstable();
+
+        let mut start = 0;
+        while start < records.len() {
+            let end = start
+                + records[start..]
+                    .iter()
+                    .take_while(|record| **record == records[start])
+                    .count();
+
+            write_entry(&mut output, records[start], (end - start) as u64)?;
+
+            start = end;
+        }
+        output.flush()?;
+
+        self.buffer.clear();
+
+        Ok(())
+    }
+
+    /// Merge runs by groups of `fan_in` until at most `fan_in` runs remain, and merge them
+    fn merge(mut self) -> error::Result<Merger> {
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+
+        while self.runs.len() > self.fan_in {
+            let merger = Merger::new(self.runs.drain(..self.fan_in).collect(), self.record_len)?;
+
+            let mut output = self.create_run()?;
+            for entry in merger {
+                let (record, count) = entry?;
+                write_entry(&mut output, &record, count)?;
+            }
+            output.flush()?;
+        }
+
+        Merger::new(std::mem::take(&mut self.runs), self.record_len)
+    }
+}
+
+impl std::ops::Drop for SortedRuns {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Merge sorted runs and sum counts of same record, runs are removed when Merger is dropped
+struct Merger {
+    readers: Vec<std::io::BufReader<std::fs::File>>,
+    heap: std::collections::BinaryHeap<std::cmp::Reverse<(Vec<u8>, usize, u64)>>,
+    record_len: usize,
+    runs: Vec<std::path::PathBuf>,
+}
+
+impl Merger {
+    fn new(runs: Vec<std::path::PathBuf>, record_len: usize) -> error::Result<Self> {
+        let mut merger = Merger {
+            readers: Vec::with_capacity(runs.len()),
+            heap: std::collections::BinaryHeap::new(),
+            record_len,
+            runs,
+        };
+
+        for (i, path) in merger.runs.iter().enumerate() {
+            merger
+                .readers
+                .push(std::io::BufReader::new(std::fs::File::open(path)?));
+            if let Some((record, count)) = read_entry(&mut merger.readers[i], record_len)? {
+                merger.heap.push(std::cmp::Reverse((record, i, count)));
+            }
+        }
+
+        Ok(merger)
+    }
+
+    /// Get smallest entry and read next entry of its run
+    fn pop(&mut self) -> error::Result<Option<(Vec<u8>, u64)>> {
+        match self.heap.pop() {
+            Some(std::cmp::Reverse((record, run, count))) => {
+                if let Some((next, next_count)) =
+                    read_entry(&mut self.readers[run], self.record_len)?
+                {
+                    self.heap.push(std::cmp::Reverse((next, run, next_count)));
+                }
+
+                Ok(Some((record, count)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_entry(&mut self) -> error::Result<Option<(Vec<u8>, u64)>> {
+        let (record, mut count) = match self.pop()? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        while matches!(self.heap.peek(), Some(std::cmp::Reverse((next, _, _))) if next == &record) {
+            if let Some((_, other)) = self.pop()? {
+                count = count.saturating_add(other);
+            }
+        }
+
+        Ok(Some((record, count)))
+    }
+}
+
+impl std::iter::Iterator for Merger {
+    type Item = error::Result<(Vec<u8>, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+impl std::ops::Drop for Merger {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn write_entry<W>(outer: &mut W, record: &[u8], count: u64) -> error::Result<()>
+where
+    W: std::io::Write,
+{
+    outer.write_all(record)?;
+    outer.write_all(&count.to_be_bytes())?;
+
+    Ok(())
+}
+
+fn read_entry<R>(inner: &mut R, record_len: usize) -> error::Result<Option<(Vec<u8>, u64)>>
+where
+    R: std::io::BufRead,
+{
+    if inner.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+
+    let record = inner.read_n_bytes_dyn(record_len)?;
+    let count = inner.read_u64()?;
+
+    Ok(Some((record, count)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn header(canonical: bool) -> section::Header {
+        section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap()
+    }
+
+    fn kmers(output: Vec<u8>) -> error::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut kmers: Vec<(Vec<u8>, Vec<u8>)> = Kff::read(std::io::Cursor::new(output))?
+            .kmers()
+            .map(|k| k.map(|k| (k.seq(ENCODING), k.data().clone())))
+            .collect::<error::Result<Vec<_>>>()?;
+        kmers.sort();
+
+        Ok(kmers)
+    }
+
+    #[test]
+    fn records() -> error::Result<()> {
+        let fasta = b">1\nACGT\nAC\n\n>2 comment\r\nTTT\r\n>3\n";
+        let records = Records::new(&fasta[..]).collect::<error::Result<Vec<_>>>()?;
+        assert_eq!(
+            records,
+            vec![b"ACGTAC".to_vec(), b"TTT".to_vec(), b"".to_vec()]
+        );
+
+        let fastq = b"@1\nACGT\n+\n!!!!\n@2\nTT\n+2\n!!\n";
+        let records = Records::new(&fastq[..]).collect::<error::Result<Vec<_>>>()?;
+        assert_eq!(records, vec![b"ACGT".to_vec(), b"TT".to_vec()]);
+
+        let mut records = Records::new(&b"@1\nACGT\n+\n!!\n"[..]);
+        assert!(matches!(
+            records.next(),
+            Some(Err(error::Error::Kff(error::Kff::BadSequenceRecord(1, _))))
+        ));
+
+        let mut records = Records::new(&b"ACGT\n"[..]);
+        assert!(records.next().unwrap().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn forward() -> error::Result<()> {
+        let mut output = Vec::new();
+        let nb_kmer = from_reads(
+            &b">1\nACGTNACgta\n>2\nAC\n"[..],
+            &mut output,
+            header(false),
+            &Parameters::new(3),
+        )?;
+
+        assert_eq!(nb_kmer, 3);
+        assert_eq!(
+            kmers(output)?,
+            vec![
+                (b"ACG".to_vec(), vec![2]),
+                (b"CGT".to_vec(), vec![2]),
+                (b"GTA".to_vec(), vec![1]),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical() -> error::Result<()> {
+        let mut output = Vec::new();
+        from_reads(
+            &b"@1\nAAGTT\n+\n!!!!!\n"[..],
+            &mut output,
+            header(true),
+            Parameters::new(3).set_data_size(2),
+        )?;
+
+        // AAG/CTT -> AAG, AGT/ACT -> ACT, GTT/AAC -> AAC
+        assert_eq!(
+            kmers(output)?,
+            vec![
+                (b"AAC".to_vec(), vec![0, 1]),
+                (b"AAG".to_vec(), vec![0, 1]),
+                (b"ACT".to_vec(), vec![0, 1]),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sorted_storage() -> error::Result<()> {
+        let reads = b">1\nACTGACTTGACGGTACAT\n>2\nACTGACTTGACGGTACAT\n>3\nGGGGGGGG\n";
+        let directory = tempfile::tempdir()?;
+
+        let mut hash = Vec::new();
+        from_reads(&reads[..], &mut hash, header(true), &Parameters::new(5))?;
+
+        let mut sorted = Vec::new();
+        from_reads(
+            &reads[..],
+            &mut sorted,
+            header(true),
+            Parameters::new(5).set_storage(Storage::Sorted {
+                buffer: 3,
+                directory: directory.path().to_path_buf(),
+            }),
+        )?;
+
+        assert_eq!(hash, sorted);
+        assert_eq!(std::fs::read_dir(directory.path())?.count(), 0);
+
+        let kmers = kmers(sorted)?;
+        assert!(kmers.contains(&(b"CCCCC".to_vec(), vec![4])));
+        assert!(kmers.contains(&(b"ACTGA".to_vec(), vec![2])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_passes() -> error::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let mut runs = SortedRuns::new(2, directory.path().to_path_buf(), 2, 2);
+
+        for record in [[0, 3], [0, 1], [0, 2], [0, 1], [1, 0], [0, 3], [0, 2]] {
+            runs.add(&record)?;
+        }
+        assert_eq!(runs.runs.len(), 3);
+
+        // Last record is spilled, four runs are merged two by two until two remain
+        let merged = runs.merge()?;
+        assert_eq!(merged.runs.len(), 2);
+
+        assert_eq!(
+            merged.collect::<error::Result<Vec<_>>>()?,
+            vec![
+                (vec![0, 1], 2),
+                (vec![0, 2], 2),
+                (vec![0, 3], 2),
+                (vec![1, 0], 1)
+            ]
+        );
+        assert_eq!(std::fs::read_dir(directory.path())?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn minimizer_sections() -> error::Result<()> {
+        let reads = b">1\nACTGACTTGACGGTACAT\n";
+
+        let mut raw = Vec::new();
+        from_reads(&reads[..], &mut raw, header(false), &Parameters::new(5))?;
+
+        let mut output = Vec::new();
+        from_reads(
+            &reads[..],
+            &mut output,
+            header(false),
+            Parameters::new(5).set_m(Some(3)).set_max(1),
+        )?;
+
+        assert_eq!(kmers(raw)?, kmers(output.clone())?);
+
+        let directory = tempfile::tempdir()?;
+        let mut sorted = Vec::new();
+        from_reads(
+            &reads[..],
+            &mut sorted,
+            header(false),
+            Parameters::new(5)
+                .set_m(Some(3))
+                .set_max(1)
+                .set_storage(Storage::Sorted {
+                    buffer: 4,
+                    directory: directory.path().to_path_buf(),
+                }),
+        )?;
+        assert_eq!(sorted, output);
+
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+        while let Some(section) = reader.next_block_section() {
+            let section = section?;
+            let minimizer = section.minimizer().unwrap().clone();
+
+            for block in section.into_blocks() {
+                let offset = *block.minimizer_offset() * 2;
+                assert_eq!(
+                    &block.kmer().seq2bit()[offset..offset + 6],
+                    minimizer.as_bitslice()
+                );
+            }
+        }
+
+        assert!(matches!(
+            from_reads(
+                &reads[..],
+                Vec::new(),
+                header(false),
+                Parameters::new(2).set_m(Some(3)),
+            ),
+            Err(error::Error::Kff(error::Kff::MinimizerSizeTooLarge(3, 2)))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,494 @@
+//! Convert a binary KMC database in KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::Kff;
+use crate::KffRead as _;
+use crate::Kmer;
+
+use crate::section::values::AbcValues as _;
+
+/// Encoding of KMC, A: 0, C: 1, G: 2, T: 3, with it KMC bits are copied without change
+pub const ENCODING: u8 = 0b00011110;
+
+/// Marker at begin and end of `.kmc_pre` file
+const PRE_MARKER: &[u8; 4] = b"KMCP";
+
+/// Marker at begin and end of `.kmc_suf` file
+const SUF_MARKER: &[u8; 4] = b"KMCS";
+
+/// Largest signature length used by KMC
+const MAX_SIGNATURE_LENGTH: u32 = 11;
+
+/// Prefix must fit in a 64 bits integer
+const MAX_PREFIX_LENGTH: u64 = 31;
+
+/// Parameters and prefix table of a KMC database, read from `.kmc_pre` file
+///
+/// KMC 1 (version 0) and KMC 2 or 3 (version 0x200) databases with counters are supported,
+/// databases with quality aware counters aren't.
+#[derive(
+    getset::Getters, std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+#[getset(get = "pub")]
+pub struct Database {
+    /// Version of KMC database
+    version: u32,
+
+    /// Size of kmer
+    k: u64,
+
+    /// Number of bytes of each counter
+    counter_size: usize,
+
+    /// Number of nucleotides store in prefix
+    prefix_length: u64,
+
+    /// Database contains canonical kmers
+    canonical: bool,
+
+    /// Minimal count of kmers
+    min_count: u32,
+
+    /// Maximal count of kmers
+    max_count: u32,
+
+    /// Number of kmers
+    total_kmers: u64,
+
+    /// Index of first suffix record of each prefix, for each signature bin
+    lut: Vec<u64>,
+}
+
+impl Database {
+    /// Read parameters and prefix table from `.kmc_pre` content
+    pub fn read<P>(mut pre: P) -> error::Result<Self>
+    where
+        P: std::io::BufRead + std::io::Seek,
+    {
+        let file_size = pre.seek(std::io::SeekFrom::End(0))?;
+        if file_size < 20 {
+            return Err(bad("prefix file is too short"));
+        }
+
+        pre.seek(std::io::SeekFrom::Start(0))?;
+        if &pre.read_n_bytes::<4>()? != PRE_MARKER {
+            return Err(bad("prefix file doesn't begin with KMCP"));
+        }
+
+        pre.seek(std::io::SeekFrom::End(-12))?;
+        let version = u32::from_le_bytes(pre.read_n_bytes::<4>()?);
+        let header_offset = u32::from_le_bytes(pre.read_n_bytes::<4>()?) as u64;
+        if &pre.read_n_bytes::<4>()? != PRE_MARKER {
+            return Err(bad("prefix file doesn't end with KMCP"));
+        }
+
+        // header_offset count header fields and version
+        if header_offset + 8 + 4 > file_size {
+            return Err(bad("header offset is larger than prefix file"));
+        }
+        pre.seek(std::io::SeekFrom::End(-((header_offset + 8) as i64)))?;
+        let header = pre.read_n_bytes_dyn(header_offset as usize)?;
+        let mut fields = Fields(&header);
+
+        let k = fields.u32()? as u64;
+        let mode = fields.u32()?;
+        let counter_size = fields.u32()? as usize;
+        let prefix_length = fields.u32()? as u64;
+        let signature_length = match version {
+            0 => None,
+            0x200 => Some(fields.u32()?),
+            _ => return Err(bad(&format!("version {:#x} isn't supported", version))),
+        };
+        let min_count = fields.u32()?;
+        let max_count = fields.u32()?;
+        let total_kmers = fields.u64()?;
+        // KMC store 0 if kmers are canonical
+        let canonical = fields.u8()? == 0;
+
+        if mode != 0 {
+            return Err(bad("quality aware counters aren't supported"));
+        }
+        if k == 0 || prefix_length > k || (k - prefix_length) % 4 != 0 {
+            return Err(bad(&format!(
+                "prefix length {} isn't compatible with k {}",
+                prefix_length, k
+            )));
+        }
+        if prefix_length > MAX_PREFIX_LENGTH {
+            return Err(bad(&format!(
+                "prefix length {} is too large",
+                prefix_length
+            )));
+        }
+        if counter_size > 4 {
+            return Err(bad(&format!("counter size {} is too large", counter_size)));
+        }
+
+        let signature_map_size = match signature_length {
+            Some(length) if length > MAX_SIGNATURE_LENGTH => {
+                return Err(bad(&format!("signature length {} is too large", length)))
+            }
+            Some(length) => ((1u64 << (2 * length)) + 1) * 4,
+            None => 0,
+        };
+        let lut_size = signature_map_size
+            .checked_add(header_offset + 8)
+            .and_then(|size| (file_size - 4).checked_sub(size))
+            .filter(|size| size % 8 == 0)
+            .ok_or_else(|| bad("prefix table size isn't valid"))?;
+
+        pre.seek(std::io::SeekFrom::Start(4))?;
+        let lut = pre
+            .read_n_bytes_dyn(lut_size as usize)?
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+
+        Ok(Self {
+            version,
+            k,
+            counter_size,
+            prefix_length,
+            canonical,
+            min_count,
+            max_count,
+            total_kmers,
+            lut,
+        })
+    }
+
+    /// Build a KFF header with encoding [ENCODING] and canonical flag of database
+    pub fn header(&self) -> error::Result<section::Header> {
+        section::Header::new(
+            1,
+            0,
+            ENCODING,
+            true,
+            self.canonical,
+            b"producer: kmc".to_vec(),
+        )
+    }
+
+    /// Read suffix records from `.kmc_suf` content and write them in `outer`, return number of kmer write
+    ///
+    /// Each prefix of each signature bin produce Minimizer sections of at most
+    /// [KMER_PER_SECTION](super::text::KMER_PER_SECTION) kmers, minimizer is the prefix and its
+    /// offset is 0, blocks contains one kmer. If prefix length is 0 Raw sections are written.
+    /// Counters are written in `counter_size` bytes as big endian. An index and a footer are
+    /// written at end.
+    pub fn to_kff<S, W>(&self, suf: S, outer: W) -> error::Result<u64>
+    where
+        S: std::io::Read,
+        W: std::io::Write + crate::KffWrite,
+    {
+        let mut suf = std::io::BufReader::new(suf);
+        if &suf.read_n_bytes::<4>()? != SUF_MARKER {
+            return Err(bad("suffix file doesn't begin with KMCS"));
+        }
+
+        let mut output = Kff::write(outer, self.header()?)?;
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), self.k);
+        if self.prefix_length != 0 {
+            values.insert("m".to_string(), self.prefix_length);
+        }
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), self.counter_size as u64);
+        values.insert("ordered".to_string(), true as u64);
+        output.write_values(values.clone())?;
+
+        let nb_prefix = 1u64 << (2 * self.prefix_length);
+        let suffix_size = ((self.k - self.prefix_length) / 4) as usize;
+        let mut nb_kmer = 0;
+
+        for (i, start) in self.lut.iter().enumerate() {
+            let end = self.lut.get(i + 1).unwrap_or(&self.total_kmers);
+            if end < start || *end > self.total_kmers || *start != nb_kmer {
+                return Err(bad("prefix table isn't sorted"));
+            }
+
+            let prefix = prefix2bits(i as u64 % nb_prefix, self.prefix_length as usize);
+
+            let mut remain = end - start;
+            while remain != 0 {
+                let chunk = remain.min(super::text::KMER_PER_SECTION as u64);
+
+                let mut blocks = Vec::with_capacity(chunk as usize);
+                for _ in 0..chunk {
+                    let mut seq2bit = bitvec::vec::BitVec::with_capacity(self.k as usize * 2);
+                    seq2bit.extend_from_bitslice(&prefix);
+                    seq2bit.extend_from_raw_slice(&suf.read_n_bytes_dyn(suffix_size)?);
+
+                    let mut data = suf.read_n_bytes_dyn(self.counter_size)?;
+                    data.reverse();
+
+                    blocks.push(section::Block::new(
+                        self.k,
+                        self.counter_size,
+                        Kmer::new(seq2bit.into_boxed_bitslice(), data),
+                        0,
+                    ));
+                }
+
+                if self.prefix_length == 0 {
+                    output.write_raw(section::Raw::new(&values)?, &blocks)?;
+                } else {
+                    output.write_minimizer(
+                        section::Minimizer::new(&values)?,
+                        prefix.clone(),
+                        &blocks,
+                    )?;
+                }
+
+                remain -= chunk;
+                nb_kmer += chunk;
+            }
+        }
+
+        if nb_kmer != self.total_kmers {
+            return Err(bad("prefix table doesn't cover all kmers"));
+        }
+        if &suf.read_n_bytes::<4>()? != SUF_MARKER {
+            return Err(bad("suffix file doesn't end with KMCS"));
+        }
+
+        output.finalize_with_index()?;
+
+        Ok(nb_kmer)
+    }
+}
+
+/// Convert KMC database `path`, path without `.kmc_pre` and `.kmc_suf` extension, in KFF file, return number of kmer write
+///
+/// See [Database::to_kff] for the layout of output.
+pub fn from_kmc<P, W>(path: P, outer: W) -> error::Result<u64>
+where
+    P: std::convert::AsRef<std::path::Path>,
+    W: std::io::Write + crate::KffWrite,
+{
+    let path = path.as_ref().as_os_str();
+
+    let mut pre_path = path.to_os_string();
+    pre_path.push(".kmc_pre");
+    let mut suf_path = path.to_os_string();
+    suf_path.push(".kmc_suf");
+
+    let database = Database::read(std::io::BufReader::new(std::fs::File::open(pre_path)?))?;
+
+    database.to_kff(std::fs::File::open(suf_path)?, outer)
+}
+
+/// Little endian reader of header fields
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn take(&mut self, len: usize) -> error::Result<&[u8]> {
+        if self.0.len() < len {
+            return Err(bad("header is too short"));
+        }
+
+        let (field, remain) = self.0.split_at(len);
+        self.0 = remain;
+
+        Ok(field)
+    }
+
+    fn u8(&mut self) -> error::Result<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> error::Result<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> error::Result<u64> {
+        self.take(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+    }
+}
+
+/// Convert a prefix in its 2 bits representation
+fn prefix2bits(prefix: u64, length: usize) -> crate::Seq2Bit {
+    let mut bits = bitvec::vec::BitVec::with_capacity(length * 2);
+    for i in (0..length * 2).rev() {
+        bits.push((prefix >> i) & 1 == 1);
+    }
+
+    bits.into_boxed_bitslice()
+}
+
+fn bad(msg: &str) -> error::Error {
+    error::Kff::BadKmcDatabase(msg.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(seq: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; (seq.len() + 3) / 4];
+        for (i, nuc) in seq.iter().enumerate() {
+            let code = b"ACGT".iter().position(|n| n == nuc).unwrap() as u8;
+            bytes[i / 4] |= code << (6 - (i % 4) * 2);
+        }
+
+        bytes
+    }
+
+    /// Build a KMC 2 database, k is 5, prefix length 1, signature length 1, two bins
+    fn database(canonical: bool) -> (Vec<u8>, Vec<u8>) {
+        // (bin, kmer, count)
+        let records: [(usize, &[u8], u16); 4] = [
+            (0, b"ACGTA", 3),
+            (0, b"CAAAA", 300),
+            (1, b"AAAAA", 1),
+            (1, b"TTTTT", 2),
+        ];
+
+        // lut[i] is number of kmers before prefix i % 4 of bin i / 4
+        let lut: Vec<u64> = (0..8)
+            .map(|i| {
+                records
+                    .iter()
+                    .filter(|(bin, seq, _)| bin * 4 + ((encode(&seq[..1])[0] >> 6) as usize) < i)
+                    .count() as u64
+            })
+            .collect();
+
+        let mut pre = PRE_MARKER.to_vec();
+        for entry in lut.iter().chain(std::iter::once(&(records.len() as u64))) {
+            pre.extend(entry.to_le_bytes());
+        }
+        pre.extend(vec![0u8; 5 * 4]);
+
+        let mut header = Vec::new();
+        for field in [5u32, 0, 2, 1, 1, 1, 65535] {
+            header.extend(field.to_le_bytes());
+        }
+        header.extend((records.len() as u64).to_le_bytes());
+        header.push(!canonical as u8);
+        header.extend([0; 27]);
+        header.extend(0x200u32.to_le_bytes());
+        pre.extend(&header);
+        pre.extend((header.len() as u32).to_le_bytes());
+        pre.extend(PRE_MARKER);
+
+        let mut suf = SUF_MARKER.to_vec();
+        for (_, seq, count) in records.iter() {
+            suf.extend(encode(&seq[1..]));
+            suf.extend(count.to_le_bytes());
+        }
+        suf.extend(SUF_MARKER);
+
+        (pre, suf)
+    }
+
+    #[test]
+    fn read_database() -> error::Result<()> {
+        let (pre, _) = database(true);
+        let database = Database::read(std::io::Cursor::new(pre))?;
+
+        assert_eq!(database.version(), &0x200);
+        assert_eq!(database.k(), &5);
+        assert_eq!(database.counter_size(), &2);
+        assert_eq!(database.prefix_length(), &1);
+        assert!(database.canonical());
+        assert_eq!(database.total_kmers(), &4);
+        assert_eq!(database.lut().len(), 9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn convert() -> error::Result<()> {
+        let (pre, suf) = database(false);
+        let database = Database::read(std::io::Cursor::new(pre))?;
+
+        let mut kff = Vec::new();
+        assert_eq!(database.to_kff(&suf[..], &mut kff)?, 4);
+
+        let mut reader = Kff::read(std::io::Cursor::new(kff.clone()))?;
+        assert!(!reader.header().canonical_kmer());
+        assert_eq!(reader.header().encoding(), &ENCODING);
+
+        let section = reader.next_block_section().unwrap()?;
+        assert_eq!(section.minimizer(), Some(&prefix2bits(0, 1)));
+        assert_eq!(reader.values().get("m"), Some(&1));
+        assert_eq!(reader.values().get("data_size"), Some(&2));
+
+        let mut text = Vec::new();
+        super::super::to_text(
+            Kff::read(std::io::Cursor::new(kff))?,
+            &mut text,
+            super::super::text::Format::Tabulated,
+        )?;
+        assert_eq!(text, b"ACGTA\t3\nCAAAA\t300\nAAAAA\t1\nTTTTT\t2\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_database() -> error::Result<()> {
+        let (mut pre, suf) = database(true);
+
+        let database = Database::read(std::io::Cursor::new(pre.clone()))?;
+        assert!(matches!(
+            database.to_kff(&suf[..suf.len() - 1], Vec::new()),
+            Err(error::Error::Kff(error::Kff::BadKmcDatabase(_))) | Err(error::Error::Io(_))
+        ));
+
+        pre[0] = b'X';
+        assert!(matches!(
+            Database::read(std::io::Cursor::new(pre)),
+            Err(error::Error::Kff(error::Kff::BadKmcDatabase(_)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_header() {
+        let (pre, _) = database(true);
+        // Header fields and version are before header offset and end marker
+        let header = pre.len() - 8 - 68;
+
+        let patch = |fields: &[(usize, u32)]| {
+            let mut pre = pre.clone();
+            for (offset, value) in fields {
+                pre[header + offset..header + offset + 4].copy_from_slice(&value.to_le_bytes());
+            }
+            Database::read(std::io::Cursor::new(pre))
+        };
+
+        // Signature length
+        assert!(matches!(
+            patch(&[(16, 32)]),
+            Err(error::Error::Kff(error::Kff::BadKmcDatabase(_)))
+        ));
+
+        // k and prefix length
+        assert!(matches!(
+            patch(&[(0, 200), (12, 36)]),
+            Err(error::Error::Kff(error::Kff::BadKmcDatabase(_)))
+        ));
+
+        // Header stop before canonical flag, version is the high half of total kmers
+        let mut truncated = pre[..header].to_vec();
+        truncated.extend(&pre[header..header + 32]);
+        truncated.extend(0x200u32.to_le_bytes());
+        truncated.extend(36u32.to_le_bytes());
+        truncated.extend(PRE_MARKER);
+        assert!(matches!(
+            Database::read(std::io::Cursor::new(truncated)),
+            Err(error::Error::Kff(error::Kff::BadKmcDatabase(_)))
+        ));
+    }
+}
@@ -0,0 +1,645 @@
+//! Constant time lookup of kmer data with a minimal perfect hash function
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::kmer::splitmix64;
+use crate::Kff;
+use crate::Seq2Bit;
+
+/// Magic number at begin of a saved kmer map
+const MAGIC: &[u8; 4] = b"KFFM";
+
+/// Maximal number of level, kmers still in collision after last level are store in a fallback map
+pub const MAX_LEVEL: u64 = 32;
+
+/// Map from kmers to their data build on a minimal perfect hash function
+///
+/// Minimal perfect hash function follow BBHash: at each level, kmers without collision in a bit
+/// array of `gamma` times number of remaining kmers bits get a position, others go to next
+/// level. Index of a kmer is the rank of its bit in all levels. A 32 bits fingerprint of each kmer
+/// is stored to reject kmers not in set, false positive rate is about 2^-32. Data are stored in
+/// a dense array. Distinct kmers with the same 64 bits hash can't be separated by the function,
+/// they are stored with their sequence and data in a collision map. If map is canonical, kmers
+/// are replaced by minimum of kmer and its reverse complement in 2 bits before insertion and
+/// lookup. If a kmer is present many times, data of first occurrence is kept.
+#[derive(
+    getset::Getters, std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+#[getset(get = "pub")]
+pub struct KmerMap {
+    /// Size of kmer
+    k: u64,
+
+    /// Encoding of kmers
+    encoding: u8,
+
+    /// Kmers are canonicalized
+    canonical: bool,
+
+    /// Size of data of each kmer
+    data_size: usize,
+
+    /// Bits of each level
+    #[getset(skip)]
+    levels: Vec<Vec<u64>>,
+
+    /// Number of set bits before each word of each level
+    #[getset(skip)]
+    ranks: Vec<Vec<u64>>,
+
+    /// Index of kmers in collision in all levels
+    #[getset(skip)]
+    fallback: std::collections::BTreeMap<u64, u64>,
+
+    /// Kmers and data of each hash shared by distinct kmers
+    #[getset(skip)]
+    collisions: std::collections::BTreeMap<u64, Vec<(Seq2Bit, Vec<u8>)>>,
+
+    /// Fingerprint of each kmer
+    #[getset(skip)]
+    fingerprints: Vec<u32>,
+
+    /// Data of each kmer
+    #[getset(skip)]
+    data: Vec<u8>,
+}
+
+impl KmerMap {
+    /// Build a map from kmers hash, as compute by [hash](crate::kmer::hash), kmers, canonical if
+    /// map is canonical, and data
+    fn new(
+        k: u64,
+        encoding: u8,
+        canonical: bool,
+        data_size: usize,
+        mut input: Vec<(u64, Seq2Bit, Vec<u8>)>,
+        gamma: f64,
+    ) -> Self {
+        // Stable sort keep first occurrence of a kmer
+        input.sort_by_key(|(hash, _, _)| *hash);
+
+        let mut kmers: Vec<(u64, Vec<u8>)> = Vec::with_capacity(input.len());
+        let mut collisions: std::collections::BTreeMap<u64, Vec<(Seq2Bit, Vec<u8>)>> =
+            std::collections::BTreeMap::new();
+        let mut group: Vec<(Seq2Bit, Vec<u8>)> = Vec::new();
+        let mut input = input.into_iter().peekable();
+        while let Some((hash, kmer, data)) = input.next() {
+            if !group.iter().any(|(seen, _)| seen == &kmer) {
+                group.push((kmer, data));
+            }
+
+            if input.peek().map(|(next, _, _)| *next) != Some(hash) {
+                if group.len() == 1 {
+                    kmers.extend(group.drain(..).map(|(_, data)| (hash, data)));
+                } else {
+                    collisions.insert(hash, std::mem::take(&mut group));
+                }
+            }
+        }
+
+        let mut levels = Vec::new();
+        let mut remaining: Vec<u64> = kmers.iter().map(|(hash, _)| *hash).collect();
+        for level in 0..MAX_LEVEL {
+            if remaining.is_empty() {
+                break;
+            }
+
+            let nb_word = ((remaining.len() as f64 * gamma / 64.0).ceil() as usize).max(1);
+            let nb_bits = nb_word as u64 * 64;
+
+            let mut seen = vec![0u64; nb_word];
+            let mut collision = vec![0u64; nb_word];
+            for hash in remaining.iter() {
+                let position = level_hash(*hash, level) % nb_bits;
+                let (word, bit) = ((position / 64) as usize, 1 << (position % 64));
+
+                if seen[word] & bit != 0 {
+                    collision[word] |= bit;
+                } else {
+                    seen[word] |= bit;
+                }
+            }
+
+            remaining.retain(|hash| {
+                let position = level_hash(*hash, level) % nb_bits;
+                collision[(position / 64) as usize] & (1 << (position % 64)) != 0
+            });
+
+            levels.push(
+                seen.iter()
+                    .zip(collision.iter())
+                    .map(|(s, c)| s & !c)
+                    .collect(),
+            );
+        }
+
+        let ranks = compute_ranks(&levels);
+        let nb_placed: u64 = levels
+            .iter()
+            .flatten()
+            .map(|word: &u64| word.count_ones() as u64)
+            .sum();
+        let fallback = remaining
+            .into_iter()
+            .enumerate()
+            .map(|(i, hash)| (hash, nb_placed + i as u64))
+            .collect();
+
+        let mut map = Self {
+            k,
+            encoding,
+            canonical,
+            data_size,
+            levels,
+            ranks,
+            fallback,
+            collisions,
+            fingerprints: vec![0; kmers.len()],
+            data: vec![0; kmers.len() * data_size],
+        };
+
+        for (hash, data) in kmers {
+            let index = map.index(hash).expect("all kmers have an index") as usize;
+
+            map.fingerprints[index] = fingerprint(hash);
+            map.data[index * data_size..(index + 1) * data_size].copy_from_slice(&data);
+        }
+
+        map
+    }
+
+    /// Number of kmer in map
+    pub fn len(&self) -> usize {
+        self.fingerprints.len() + self.collisions.values().map(Vec::len).sum::<usize>()
+    }
+
+    /// Return true if map is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get data of kmer, None if kmer isn't in map
+    pub fn get(&self, kmer: &Seq2Bit) -> Option<&[u8]> {
+        if kmer.len() as u64 != self.k * 2 {
+            return None;
+        }
+
+        let hash = crate::kmer::hash(kmer, self.encoding, self.canonical);
+        if self.collisions.contains_key(&hash) {
+            let kmer = if self.canonical {
+                crate::kmer::canonical(kmer, self.encoding)
+            } else {
+                kmer.clone()
+            };
+
+            return self.get_hash(hash, &kmer);
+        }
+
+        self.get_hash(hash, kmer)
+    }
+
+    /// Get data of kmer with its hash, kmer must be canonical if map is canonical
+    fn get_hash(&self, hash: u64, kmer: &Seq2Bit) -> Option<&[u8]> {
+        if let Some(group) = self.collisions.get(&hash) {
+            return group
+                .iter()
+                .find(|(seen, _)| seen == kmer)
+                .map(|(_, data)| data.as_slice());
+        }
+
+        let index = self.index(hash)? as usize;
+
+        if self.fingerprints.get(index) != Some(&fingerprint(hash)) {
+            return None;
+        }
+
+        Some(&self.data[index * self.data_size..(index + 1) * self.data_size])
+    }
+
+    /// Return true if kmer is in map
+    pub fn contains(&self, kmer: &Seq2Bit) -> bool {
+        self.get(kmer).is_some()
+    }
+
+    /// Get index of a kmer hash, kmers not in map could get an index
+    fn index(&self, hash: u64) -> Option<u64> {
+        for (level, bits) in self.levels.iter().enumerate() {
+            let position = level_hash(hash, level as u64) % (bits.len() as u64 * 64);
+            let (word, bit) = ((position / 64) as usize, position % 64);
+
+            if bits[word] & (1 << bit) != 0 {
+                let before = bits[word] & ((1 << bit) - 1);
+                return Some(self.ranks[level][word] + before.count_ones() as u64);
+            }
+        }
+
+        self.fallback.get(&hash).cloned()
+    }
+
+    /// Read a KmerMap
+    pub fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + crate::KffRead,
+    {
+        if &inner.read_n_bytes::<4>()? != MAGIC {
+            return Err(error::Kff::NotAKmerMap.into());
+        }
+
+        let k = inner.read_u64()?;
+        let encoding = inner.read_u8()?;
+        let canonical = inner.read_bool()?;
+        let data_size = inner.read_u64()? as usize;
+        let nb_kmer = inner.read_u64()? as usize;
+        let data_len = nb_kmer
+            .checked_mul(data_size)
+            .ok_or_else(|| bad_map("data size is too large"))?;
+
+        let nb_level = inner.read_u64()?;
+        if nb_level > MAX_LEVEL {
+            return Err(bad_map(&format!(
+                "{} levels is more than {}",
+                nb_level, MAX_LEVEL
+            )));
+        }
+
+        let mut levels = Vec::new();
+        for _ in 0..nb_level {
+            let nb_word = inner.read_u64()?;
+            if nb_word == 0 {
+                return Err(bad_map("level is empty"));
+            }
+
+            levels.push(
+                (0..nb_word)
+                    .map(|_| inner.read_u64())
+                    .collect::<error::Result<Vec<u64>>>()?,
+            );
+        }
+
+        let nb_fallback = inner.read_u64()?;
+        let fallback = (0..nb_fallback)
+            .map(|_| Ok((inner.read_u64()?, inner.read_u64()?)))
+            .collect::<error::Result<std::collections::BTreeMap<u64, u64>>>()?;
+
+        let nb_collision = inner.read_u64()?;
+        let mut collisions: std::collections::BTreeMap<u64, Vec<(Seq2Bit, Vec<u8>)>> =
+            std::collections::BTreeMap::new();
+        for _ in 0..nb_collision {
+            let hash = inner.read_u64()?;
+            let kmer = inner.read_2bits(k as usize)?.into_boxed_bitslice();
+            let data = inner.read_n_bytes_dyn(data_size)?;
+
+            collisions.entry(hash).or_default().push((kmer, data));
+        }
+
+        let fingerprints = (0..nb_kmer)
+            .map(|_| inner.read_u32())
+            .collect::<error::Result<Vec<u32>>>()?;
+        let data = inner.read_n_bytes_dyn(data_len)?;
+
+        Ok(Self {
+            k,
+            encoding,
+            canonical,
+            data_size,
+            ranks: compute_ranks(&levels),
+            levels,
+            fallback,
+            collisions,
+            fingerprints,
+            data,
+        })
+    }
+
+    /// Write a KmerMap
+    pub fn write<W>(&self, outer: &mut W) -> error::Result<()>
+    where
+        W: std::io::Write + crate::KffWrite,
+    {
+        outer.write_bytes(MAGIC)?;
+        outer.write_u64(&self.k)?;
+        outer.write_u8(&self.encoding)?;
+        outer.write_bool(&self.canonical)?;
+        outer.write_u64(&(self.data_size as u64))?;
+        outer.write_u64(&(self.fingerprints.len() as u64))?;
+
+        outer.write_u64(&(self.levels.len() as u64))?;
+        for bits in self.levels.iter() {
+            outer.write_u64(&(bits.len() as u64))?;
+            for word in bits {
+                outer.write_u64(word)?;
+            }
+        }
+
+        outer.write_u64(&(self.fallback.len() as u64))?;
+        for (hash, index) in self.fallback.iter() {
+            outer.write_u64(hash)?;
+            outer.write_u64(index)?;
+        }
+
+        outer.write_u64(&((self.len() - self.fingerprints.len()) as u64))?;
+        for (hash, group) in self.collisions.iter() {
+            for (kmer, data) in group {
+                outer.write_u64(hash)?;
+                outer.write_bytes(kmer.as_raw_slice())?;
+                outer.write_bytes(data)?;
+            }
+        }
+
+        for fingerprint in self.fingerprints.iter() {
+            outer.write_u32(fingerprint)?;
+        }
+
+        outer.write_bytes(&self.data)
+    }
+
+    /// Read a KmerMap from file match with path
+    pub fn load<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut inner = std::io::BufReader::new(std::fs::File::open(path)?);
+
+        Self::read(&mut inner)
+    }
+
+    /// Write a KmerMap in file match with path
+    pub fn save<P>(&self, path: P) -> error::Result<()>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut outer = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+        self.write(&mut outer)?;
+        std::io::Write::flush(&mut outer)?;
+
+        Ok(())
+    }
+}
+
+impl<R> Kff<R>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+{
+    /// Build a [KmerMap] of all kmers, `gamma` is the ratio between bits and kmers at each level
+    ///
+    /// A larger `gamma` build faster and lookup faster a larger map, 2 is a good default. Map is
+    /// canonical if header canonical flag is set, `k` is size of first kmer. All kmers must have
+    /// the same data size. Kmer, hash and data of each kmer are kept in memory during construction.
+    pub fn build_map(self, gamma: f64) -> error::Result<KmerMap> {
+        if !(gamma >= 1.0 && gamma.is_finite()) {
+            return Err(error::Kff::BadGamma(gamma).into());
+        }
+
+        let encoding = *self.header().encoding();
+        let canonical = *self.header().canonical_kmer();
+
+        let mut k = 0;
+        let mut data_size = 0;
+        let mut kmers = Vec::new();
+        for kmer in self.kmers() {
+            let kmer = kmer?;
+
+            if kmers.is_empty() {
+                k = kmer.seq2bit().len() as u64 / 2;
+                data_size = kmer.data().len();
+            } else if kmer.data().len() != data_size {
+                return Err(error::Kff::DataSizeMismatch(data_size, kmer.data().len()).into());
+            }
+
+            let seq = if canonical {
+                crate::kmer::canonical(kmer.seq2bit(), encoding)
+            } else {
+                kmer.seq2bit().clone()
+            };
+
+            kmers.push((
+                crate::kmer::hash(&seq, encoding, false),
+                seq,
+                kmer.data().clone(),
+            ));
+        }
+
+        Ok(KmerMap::new(
+            k, encoding, canonical, data_size, kmers, gamma,
+        ))
+    }
+}
+
+/// Number of set bits before each word of each level, levels are concatenated
+fn compute_ranks(levels: &[Vec<u64>]) -> Vec<Vec<u64>> {
+    let mut rank = 0;
+
+    levels
+        .iter()
+        .map(|bits| {
+            bits.iter()
+                .map(|word| {
+                    let before = rank;
+                    rank += word.count_ones() as u64;
+                    before
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn level_hash(hash: u64, level: u64) -> u64 {
+    splitmix64(hash ^ (level + 1).wrapping_mul(0x9e3779b97f4a7c15))
+}
+
+fn fingerprint(hash: u64) -> u32 {
+    (splitmix64(hash ^ 0x5bd1e9955bd1e995) >> 32) as u32
+}
+
+fn bad_map(msg: &str) -> error::Error {
+    error::Kff::BadKmerMap(msg.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section;
+    use crate::section::values::AbcValues as _;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file(canonical: bool, kmers: &[(Vec<u8>, u16)]) -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, false, canonical, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let k = kmers.first().map(|(seq, _)| seq.len()).unwrap_or(5) as u64;
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), k);
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), 2);
+        writer.write_values(values.clone()).unwrap();
+
+        let blocks: Vec<section::Block> = kmers
+            .iter()
+            .map(|(seq, count)| {
+                section::Block::new(
+                    k,
+                    2,
+                    Kmer::from_ascii(seq, count.to_be_bytes().to_vec(), ENCODING),
+                    0,
+                )
+            })
+            .collect();
+        writer
+            .write_raw(section::Raw::new(&values).unwrap(), &blocks)
+            .unwrap();
+        writer.finalize().unwrap();
+
+        writer.into_inner()
+    }
+
+    fn random_kmers(rng: &mut rand::rngs::StdRng, nb: usize) -> Vec<Vec<u8>> {
+        use rand::Rng as _;
+
+        (0..nb)
+            .map(|_| (0..21).map(|_| b"ACGT"[rng.gen_range(0..4)]).collect())
+            .collect()
+    }
+
+    fn seq(seq: &[u8]) -> Seq2Bit {
+        crate::kmer::seq2bits(seq, ENCODING)
+    }
+
+    #[test]
+    fn build() -> error::Result<()> {
+        let kmers = vec![
+            (b"ACTGA".to_vec(), 3),
+            (b"TTTTT".to_vec(), 258),
+            (b"ACTGA".to_vec(), 4),
+        ];
+        let map = Kff::read(std::io::Cursor::new(file(false, &kmers)))?.build_map(2.0)?;
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.data_size(), &2);
+        assert_eq!(map.get(&seq(b"ACTGA")), Some(&[0, 3][..]));
+        assert_eq!(map.get(&seq(b"TTTTT")), Some(&[1, 2][..]));
+        assert_eq!(map.get(&seq(b"TCAGT")), None);
+        assert_eq!(map.get(&seq(b"ACTG")), None);
+
+        assert!(matches!(
+            Kff::read(std::io::Cursor::new(file(false, &kmers)))?.build_map(0.5),
+            Err(error::Error::Kff(error::Kff::BadGamma(_)))
+        ));
+
+        let empty = Kff::read(std::io::Cursor::new(file(false, &[])))?.build_map(2.0)?;
+        assert!(empty.is_empty());
+        assert!(!empty.contains(&seq(b"ACTGA")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical() -> error::Result<()> {
+        let kmers = vec![(b"ACTGA".to_vec(), 3)];
+        let map = Kff::read(std::io::Cursor::new(file(true, &kmers)))?.build_map(2.0)?;
+
+        assert_eq!(map.get(&seq(b"TCAGT")), Some(&[0, 3][..]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn many_kmers() -> error::Result<()> {
+        use rand::SeedableRng as _;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut seqs = random_kmers(&mut rng, 5000);
+        seqs.sort_unstable();
+        seqs.dedup();
+
+        let kmers: Vec<(Vec<u8>, u16)> = seqs
+            .iter()
+            .enumerate()
+            .map(|(i, seq)| (seq.clone(), i as u16))
+            .collect();
+        let map = Kff::read(std::io::Cursor::new(file(false, &kmers)))?.build_map(1.0)?;
+
+        assert_eq!(map.len(), kmers.len());
+        for (seq, count) in kmers.iter() {
+            assert_eq!(
+                map.get(&crate::kmer::seq2bits(seq, ENCODING)),
+                Some(&count.to_be_bytes()[..])
+            );
+        }
+
+        let false_positive = random_kmers(&mut rng, 10000)
+            .iter()
+            .filter(|kmer| seqs.binary_search(kmer).is_err())
+            .filter(|kmer| map.contains(&crate::kmer::seq2bits(kmer, ENCODING)))
+            .count();
+        assert!(false_positive <= 1, "{} false positives", false_positive);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_write() -> error::Result<()> {
+        use rand::SeedableRng as _;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let kmers: Vec<(Vec<u8>, u16)> = random_kmers(&mut rng, 100).into_iter().zip(0..).collect();
+        let map = Kff::read(std::io::Cursor::new(file(false, &kmers)))?.build_map(1.5)?;
+
+        let mut buffer = Vec::new();
+        map.write(&mut buffer)?;
+        assert_eq!(&buffer[..4], b"KFFM");
+
+        assert_eq!(
+            KmerMap::read(&mut std::io::Cursor::new(buffer.clone()))?,
+            map
+        );
+
+        assert!(matches!(
+            KmerMap::read(&mut std::io::Cursor::new(b"KFFB".to_vec())),
+            Err(error::Error::Kff(error::Kff::NotAKmerMap))
+        ));
+
+        // Data size, number of level and number of word of first level
+        for (offset, value) in [(14, u64::MAX), (30, u64::MAX), (38, 0)] {
+            let mut bad = buffer.clone();
+            bad[offset..offset + 8].copy_from_slice(&value.to_be_bytes());
+            assert!(matches!(
+                KmerMap::read(&mut std::io::Cursor::new(bad)),
+                Err(error::Error::Kff(error::Kff::BadKmerMap(_)))
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn collision() -> error::Result<()> {
+        // Hash are forced, ACTGA and GGGGG share the same
+        let input = vec![
+            (1, seq(b"ACTGA"), vec![1]),
+            (2, seq(b"TTTTT"), vec![2]),
+            (1, seq(b"GGGGG"), vec![3]),
+            (1, seq(b"ACTGA"), vec![4]),
+        ];
+        let map = KmerMap::new(5, ENCODING, false, 1, input, 2.0);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get_hash(1, &seq(b"ACTGA")), Some(&[1][..]));
+        assert_eq!(map.get_hash(1, &seq(b"GGGGG")), Some(&[3][..]));
+        assert_eq!(map.get_hash(1, &seq(b"CCCCC")), None);
+        assert_eq!(map.get_hash(2, &seq(b"TTTTT")), Some(&[2][..]));
+
+        let mut buffer = Vec::new();
+        map.write(&mut buffer)?;
+        assert_eq!(KmerMap::read(&mut std::io::Cursor::new(buffer))?, map);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Query kmers of sequences against a KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section::values::AbcValues as _;
+use crate::Data;
+use crate::Kff;
+use crate::Seq2Bit;
+
+/// Data of kmer begin at each position of a sequence, None if kmer is absent
+pub type Coverage = Vec<Option<Data>>;
+
+/// Slide a window of size `k` on each sequence and search each kmer in `input`, return a [Coverage] per sequence
+///
+/// `k` is the kmer size of kmer sections, all sections must have the same `k` otherwise
+/// [error::Kff::KmerSizeMismatch] is returned, and `input` must be indexed. A kmer containing a
+/// character other than A, C, G or T (case insensitive) is absent, a sequence shorter than `k`
+/// has an empty coverage. If `input` is canonical a kmer and its reverse complement are the same.
+///
+/// Lookups are batched, each distinct kmer is searched once and each section is decoded at most
+/// once. Minimizer of a kmer is one of its m-mers whatever the way it was chosen, smallest m-mer
+/// for files write by this crate or KMC prefix for files build by [from_kmc](super::from_kmc), so
+/// only Minimizer sections whose minimizer is an m-mer of a queried kmer, or of its reverse
+/// complement if `input` is canonical, are decoded, see [Kff::minimizers]. All Raw sections are
+/// decoded.
+pub fn query<R, S>(input: &mut Kff<R>, sequences: &[S]) -> error::Result<Vec<Coverage>>
+where
+    R: std::io::Read + std::io::Seek + crate::KffRead,
+    S: std::convert::AsRef<[u8]>,
+{
+    let encoding = *input.header().encoding();
+    let canonical = *input.header().canonical_kmer();
+
+    let pairs = input
+        .index()
+        .as_ref()
+        .ok_or(error::Error::Kff(error::Kff::NoIndex))?
+        .pair()
+        .clone();
+    let kmer_sections: Vec<(usize, u8)> = pairs
+        .iter()
+        .enumerate()
+        .filter(|(_, (t, _))| matches!(t, b'r' | b'm'))
+        .map(|(n, (t, _))| (n, *t))
+        .collect();
+
+    let mut k = None;
+    for (n, _) in kmer_sections.iter() {
+        let section_k = input.values_of(*n)?.get_value("k")?;
+
+        match k {
+            None => k = Some(section_k),
+            Some(k) if k != section_k => {
+                return Err(error::Kff::KmerSizeMismatch(k, section_k).into())
+            }
+            Some(_) => (),
+        }
+    }
+    let k = k.unwrap_or(0) as usize;
+
+    // Kmer of each position, and all distinct kmers
+    let mut positions: Vec<Vec<Option<Seq2Bit>>> = Vec::with_capacity(sequences.len());
+    let mut found: rustc_hash::FxHashMap<Seq2Bit, Option<Data>> = rustc_hash::FxHashMap::default();
+    for seq in sequences {
+        let seq = seq.as_ref();
+
+        let kmers: Vec<Option<Seq2Bit>> = if k == 0 || seq.len() < k {
+            Vec::new()
+        } else {
+            seq.windows(k)
+                .map(|window| {
+                    if !window.iter().all(|nuc| b"ACGTacgt".contains(nuc)) {
+                        return None;
+                    }
+
+                    let kmer = key(crate::kmer::seq2bits(window, encoding), encoding, canonical);
+                    found.entry(kmer.clone()).or_insert(None);
+
+                    Some(kmer)
+                })
+                .collect()
+        };
+
+        positions.push(kmers);
+    }
+
+    // Sections to decode, Minimizer sections of each m-mer of queried kmers and all Raw sections
+    let mut selected: std::collections::BTreeSet<usize> = kmer_sections
+        .iter()
+        .filter(|(_, t)| *t == b'r')
+        .map(|(n, _)| *n)
+        .collect();
+
+    let minimizers = input.minimizers()?;
+    let mut sizes: Vec<usize> = minimizers.keys().map(|m| m.len() / 2).collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    for kmer in found.keys() {
+        let reverse = canonical.then(|| crate::kmer::rev_comp(kmer, encoding));
+        for strand in std::iter::once(kmer).chain(reverse.iter()) {
+            for m in sizes.iter().filter(|m| **m <= k) {
+                for offset in 0..=(k - m) {
+                    let mmer: Seq2Bit = strand[offset * 2..(offset + m) * 2].into();
+                    if let Some(sections) = minimizers.get(&mmer) {
+                        selected.extend(sections);
+                    }
+                }
+            }
+        }
+    }
+
+    for n in selected {
+        for kmer in input.kmer_of_section(n)? {
+            if let Some(data) = found.get_mut(&key(kmer.seq2bit().clone(), encoding, canonical)) {
+                *data = Some(kmer.data().clone());
+            }
+        }
+    }
+
+    Ok(positions
+        .into_iter()
+        .map(|kmers| {
+            kmers
+                .into_iter()
+                .map(|kmer| kmer.and_then(|kmer| found[&kmer].clone()))
+                .collect()
+        })
+        .collect())
+}
+
+/// Kmer used to search, kmer in a canonical file can be store in any orientation
+fn key(kmer: Seq2Bit, encoding: u8, canonical: bool) -> Seq2Bit {
+    if canonical {
+        crate::kmer::canonical(&kmer, encoding)
+    } else {
+        kmer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file(canonical: bool) -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 3);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone()).unwrap();
+
+        let block = |seq: &[u8], data: u8| {
+            let kmer = Kmer::from_ascii(seq, vec![data], ENCODING);
+            let (offset, _) = crate::kmer::minimizer(kmer.seq2bit(), 2).unwrap();
+            section::Block::new(3, 1, kmer, offset)
+        };
+
+        // Minimizer of ACT and TAC is AC, of GGT is GT
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values).unwrap(),
+                crate::kmer::seq2bits(b"AC", ENCODING),
+                &[block(b"ACT", 1), block(b"TAC", 2)],
+            )
+            .unwrap();
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values).unwrap(),
+                crate::kmer::seq2bits(b"GT", ENCODING),
+                &[block(b"GGT", 3)],
+            )
+            .unwrap();
+        writer
+            .write_raw(section::Raw::new(&values).unwrap(), &[block(b"CCC", 4)])
+            .unwrap();
+        writer.finalize_with_index().unwrap();
+
+        writer.into_inner()
+    }
+
+    #[test]
+    fn forward() -> error::Result<()> {
+        let mut input = Kff::read_with_index(std::io::Cursor::new(file(false)))?;
+
+        let coverage = query(&mut input, &[&b"TACTG"[..], b"ccccNGGT", b"AC"])?;
+
+        assert_eq!(
+            coverage,
+            vec![
+                vec![Some(vec![2]), Some(vec![1]), None],
+                vec![
+                    Some(vec![4]),
+                    Some(vec![4]),
+                    None,
+                    None,
+                    None,
+                    Some(vec![3])
+                ],
+                vec![],
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical() -> error::Result<()> {
+        let mut input = Kff::read_with_index(std::io::Cursor::new(file(true)))?;
+
+        // AGT is reverse complement of ACT, GTA of TAC, GGG of CCC
+        let coverage = query(&mut input, &[&b"AGTA"[..], b"GGG"])?;
+
+        assert_eq!(
+            coverage,
+            vec![vec![Some(vec![1]), Some(vec![2])], vec![Some(vec![4])]]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn prefix_minimizer() -> error::Result<()> {
+        for canonical in [false, true] {
+            let header =
+                section::Header::new(1, 0, ENCODING, true, canonical, b"".to_vec()).unwrap();
+            let mut writer = Kff::write(Vec::new(), header)?;
+
+            let mut values = section::Values::new();
+            values.insert("k".to_string(), 3);
+            values.insert("m".to_string(), 2);
+            values.insert("max".to_string(), 1);
+            values.insert("data_size".to_string(), 1);
+            writer.write_values(values.clone())?;
+
+            // Like KMC, minimizer is prefix of kmer, smallest m-mer of TAC is AC and GGG is
+            // store in place of its reverse complement CCC
+            for (seq, data) in [(&b"TAC"[..], 1), (b"GGG", 2)] {
+                writer.write_minimizer(
+                    section::Minimizer::new(&values)?,
+                    crate::kmer::seq2bits(&seq[..2], ENCODING),
+                    &[section::Block::new(
+                        3,
+                        1,
+                        Kmer::from_ascii(seq, vec![data], ENCODING),
+                        0,
+                    )],
+                )?;
+            }
+            writer.finalize_with_index()?;
+
+            let mut input = Kff::read_with_index(std::io::Cursor::new(writer.into_inner()))?;
+            let coverage = query(&mut input, &[&b"TAC"[..], b"CCC"])?;
+
+            if canonical {
+                assert_eq!(coverage, vec![vec![Some(vec![1])], vec![Some(vec![2])]]);
+            } else {
+                assert_eq!(coverage, vec![vec![Some(vec![1])], vec![None]]);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn different_k() -> error::Result<()> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        for (k, seq) in [(3, &b"ACT"[..]), (4, b"ACTG")] {
+            let mut values = section::Values::new();
+            values.insert("k".to_string(), k);
+            values.insert("max".to_string(), 1);
+            values.insert("data_size".to_string(), 0);
+            writer.write_values(values.clone())?;
+            writer.write_raw(
+                section::Raw::new(&values)?,
+                &[section::Block::new(
+                    k,
+                    0,
+                    Kmer::from_ascii(seq, vec![], ENCODING),
+                    0,
+                )],
+            )?;
+        }
+        writer.finalize_with_index()?;
+
+        let mut input = Kff::read_with_index(std::io::Cursor::new(writer.into_inner()))?;
+        assert!(matches!(
+            query(&mut input, &[b"ACTG"]),
+            Err(error::Error::Kff(error::Kff::KmerSizeMismatch(3, 4)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_index() -> error::Result<()> {
+        let mut input = Kff::read(std::io::Cursor::new(file(false)))?;
+
+        assert!(matches!(
+            query(&mut input, &[b"ACT"]),
+            Err(error::Error::Kff(error::Kff::NoIndex))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+//! Salvage kmer sections of a truncated or damaged KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::utils;
+use crate::Kff;
+use crate::KffRead as _;
+
+/// Where and why reading of a damaged file stop
+#[derive(getset::Getters, std::fmt::Debug)]
+#[getset(get = "pub")]
+pub struct Truncation {
+    /// Position in bytes after last kmer section fully decoded, or of incomplete final signature
+    position: u64,
+
+    /// Error that stop reading
+    error: error::Error,
+}
+
+/// Iterate over all kmer sections that can be decoded in a truncated file, with their Values
+///
+/// The first section that can't be decoded, or a missing final signature, stop iteration without
+/// error and is reported by [Recovery::truncation]. Everything after this point is lost, sections
+/// before it are yielded unchanged.
+///
+/// ```no_run
+/// # fn main() -> kff::error::Result<()> {
+/// let recovery = kff::tools::Recovery::open("crashed.kff")?;
+/// let output = std::io::BufWriter::new(std::fs::File::create("repaired.kff")?);
+///
+/// if let Some(truncation) = recovery.repair(output)? {
+///     eprintln!("lost everything after byte {}: {}", truncation.position(), truncation.error());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Recovery<R> {
+    reader: Kff<utils::read::Recorder<R>>,
+    truncation: Option<Truncation>,
+    done: bool,
+
+    /// Position in bytes after last section fully decoded, bytes of a section are recorded until
+    /// it's decoded
+    position: u64,
+}
+
+impl<R> Recovery<R>
+where
+    R: std::io::BufRead,
+{
+    /// Create a Recovery reader, header must be complete
+    pub fn new(inner: R) -> error::Result<Self> {
+        let mut reader = Kff::read(utils::read::Recorder::new(inner))?;
+        let position = reader.inner_mut().clear_record();
+
+        Ok(Self {
+            reader,
+            truncation: None,
+            done: false,
+            position,
+        })
+    }
+
+    /// Header of file
+    pub fn header(&self) -> &section::Header {
+        self.reader.header()
+    }
+
+    /// Truncation found, None if iteration isn't finished or file is complete
+    pub fn truncation(&self) -> Option<&Truncation> {
+        self.truncation.as_ref()
+    }
+
+    /// Write all decodable sections in `outer`, followed by an index, a footer and the final signature
+    ///
+    /// Return truncation of input, None if input was complete.
+    pub fn repair<W>(mut self, outer: W) -> error::Result<Option<Truncation>>
+    where
+        W: std::io::Write + crate::KffWrite,
+    {
+        let mut output = Kff::write(outer, self.header().clone())?;
+
+        for (values, section) in self.by_ref() {
+            if output.values() != &values {
+                output.write_values(values.clone())?;
+            }
+
+            match section {
+                section::KmerSection::Raw(blocks) => {
+                    output.write_raw(section::Raw::new(&values)?, &blocks)?
+                }
+                section::KmerSection::Minimizer(minimizer, blocks) => {
+                    output.write_minimizer(section::Minimizer::new(&values)?, minimizer, &blocks)?
+                }
+            }
+        }
+
+        output.finalize_with_index()?;
+
+        Ok(self.truncation)
+    }
+
+    /// Check final signature, first byte is already consume
+    fn check_signature(&mut self) -> error::Result<()> {
+        if &self.reader.inner_mut().read_n_bytes::<2>()? != b"FF" {
+            return Err(error::Kff::MissingMagic("end".to_string()).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Recovery<std::io::BufReader<std::fs::File>> {
+    /// Create a Recovery reader on file match with path
+    pub fn open<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        Self::new(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+impl<R> std::iter::Iterator for Recovery<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = (section::Values, section::KmerSection);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let position = self.position;
+        let section = self.reader.next_block_section();
+        self.position += self.reader.inner_mut().clear_record();
+
+        match section {
+            Some(Ok(section)) => Some((self.reader.values().clone(), section)),
+            Some(Err(error)) => {
+                self.done = true;
+                self.truncation = Some(Truncation { position, error });
+
+                None
+            }
+            None => {
+                self.done = true;
+
+                // Position of 'K' of final signature
+                let position = self.position - 1;
+                if let Err(error) = self.check_signature() {
+                    self.truncation = Some(Truncation { position, error });
+                }
+
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section::values::AbcValues as _;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    /// A file with a Raw and a Minimizer section, and position of end of Raw section
+    fn file() -> (Vec<u8>, usize) {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 10);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone()).unwrap();
+
+        writer
+            .write_raw(
+                section::Raw::new(&values).unwrap(),
+                &[section::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(b"ACTGAC", vec![3, 1], ENCODING),
+                    0,
+                )],
+            )
+            .unwrap();
+        let end_raw = writer.inner().len();
+
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values).unwrap(),
+                crate::kmer::seq2bits(b"TG", ENCODING),
+                &[section::Block::new(
+                    5,
+                    1,
+                    Kmer::from_ascii(b"GCTGAT", vec![2, 4], ENCODING),
+                    2,
+                )],
+            )
+            .unwrap();
+        writer.finalize().unwrap();
+
+        (writer.into_inner(), end_raw)
+    }
+
+    fn minimizers(recovery: &mut Recovery<std::io::Cursor<Vec<u8>>>) -> Vec<bool> {
+        recovery
+            .map(|(_, section)| section.minimizer().is_some())
+            .collect()
+    }
+
+    #[test]
+    fn complete() -> error::Result<()> {
+        let (input, _) = file();
+        let mut recovery = Recovery::new(std::io::Cursor::new(input))?;
+
+        assert_eq!(minimizers(&mut recovery), vec![false, true]);
+        assert!(recovery.truncation().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_section() -> error::Result<()> {
+        let (input, end_raw) = file();
+        let mut recovery = Recovery::new(std::io::Cursor::new(input[..end_raw + 5].to_vec()))?;
+
+        assert_eq!(minimizers(&mut recovery), vec![false]);
+        let truncation = recovery.truncation().unwrap();
+        assert_eq!(truncation.position(), &(end_raw as u64));
+        assert!(matches!(truncation.error(), error::Error::Io(_)));
+
+        // Next call doesn't read anymore
+        assert!(recovery.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_signature() -> error::Result<()> {
+        let (input, _) = file();
+        let end = input.len();
+        let mut recovery = Recovery::new(std::io::Cursor::new(input[..end - 1].to_vec()))?;
+
+        assert_eq!(minimizers(&mut recovery), vec![false, true]);
+        let truncation = recovery.truncation().unwrap();
+        assert_eq!(truncation.position(), &(end as u64 - 3));
+
+        // Truncated exactly at end of last section
+        let mut recovery = Recovery::new(std::io::Cursor::new(input[..end - 3].to_vec()))?;
+        assert_eq!(minimizers(&mut recovery), vec![false, true]);
+        assert_eq!(recovery.truncation().unwrap().position(), &(end as u64 - 3));
+
+        assert!(Recovery::new(std::io::Cursor::new(input[..10].to_vec())).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn repair() -> error::Result<()> {
+        let (input, end_raw) = file();
+
+        let mut output = Vec::new();
+        let truncation = Recovery::new(std::io::Cursor::new(input[..end_raw + 5].to_vec()))?
+            .repair(&mut output)?;
+        assert_eq!(truncation.unwrap().position(), &(end_raw as u64));
+
+        Kff::read(std::io::Cursor::new(output.clone()))?.validate()?;
+
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(output))?;
+        assert_eq!(reader.index().as_ref().unwrap().pair().len(), 2);
+        assert_eq!(
+            reader
+                .kmer_of_section(1)?
+                .iter()
+                .map(|kmer| kmer.seq(ENCODING))
+                .collect::<Vec<_>>(),
+            vec![b"ACTGA".to_vec(), b"CTGAC".to_vec()]
+        );
+
+        // Complete file are rewritten with an index
+        let mut output = Vec::new();
+        assert!(Recovery::new(std::io::Cursor::new(file().0))?
+            .repair(&mut output)?
+            .is_none());
+        assert_eq!(
+            Kff::read_with_index(std::io::Cursor::new(output))?
+                .index()
+                .as_ref()
+                .unwrap()
+                .pair()
+                .len(),
+            3
+        );
+
+        Ok(())
+    }
+}
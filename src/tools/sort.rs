@@ -0,0 +1,143 @@
+//! Sort kmers of a KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::Kff;
+use crate::Kmer;
+
+use crate::section::values::AbcValues as _;
+
+/// Read all kmers of `input` and write them sorted in `outer`, return number of kmer write
+///
+/// Kmers are grouped by `k` and `data_size`, groups are written in order of first appearance.
+/// In each group kmers are sorted by their 2 bits representation, so order depends on encoding,
+/// and written one per block in a Raw section with `ordered` set to true. All kmers are kept in
+/// memory.
+pub fn sort<R, W>(mut input: Kff<R>, outer: W) -> error::Result<u64>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+    W: std::io::Write + crate::KffWrite,
+{
+    let mut groups: Vec<((u64, usize), Vec<Kmer>)> = Vec::new();
+
+    while let Some(section) = input.next_block_section() {
+        for block in section?.into_blocks() {
+            let key = (*block.k(), *block.data_size());
+
+            let position = match groups.iter().position(|(k, _)| *k == key) {
+                Some(position) => position,
+                None => {
+                    groups.push((key, Vec::new()));
+                    groups.len() - 1
+                }
+            };
+
+            groups[position].1.extend(block);
+        }
+    }
+
+    let mut output = Kff::write(outer, input.header().clone())?;
+    let mut nb_kmer = 0;
+
+    for ((k, data_size), mut kmers) in groups {
+        kmers.sort_unstable_by(|a, b| a.seq2bit().cmp(b.seq2bit()));
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), k);
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), data_size as u64);
+        values.insert("ordered".to_string(), true as u64);
+        output.write_values(values.clone())?;
+
+        nb_kmer += kmers.len() as u64;
+        let blocks: Vec<section::Block> = kmers
+            .into_iter()
+            .map(|kmer| section::Block::new(k, data_size, kmer, 0))
+            .collect();
+
+        output.write_raw(section::Raw::new(&values)?, &blocks)?;
+    }
+
+    output.finalize_with_index()?;
+
+    Ok(nb_kmer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODING: u8 = 0b00011011;
+
+    #[test]
+    fn sort() -> error::Result<()> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 3);
+        values.insert("max".to_string(), 255);
+        values.insert("data_size".to_string(), 1);
+        writer.write_values(values.clone())?;
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[
+                section::Block::new(3, 1, Kmer::from_ascii(b"GTCA", vec![1, 2], ENCODING), 0),
+                section::Block::new(3, 1, Kmer::from_ascii(b"AAC", vec![3], ENCODING), 0),
+            ],
+        )?;
+
+        values.insert("k".to_string(), 2);
+        writer.write_values(values.clone())?;
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[section::Block::new(
+                2,
+                1,
+                Kmer::from_ascii(b"TA", vec![4], ENCODING),
+                0,
+            )],
+        )?;
+        writer.finalize()?;
+
+        let mut output = Vec::new();
+        assert_eq!(
+            super::sort(
+                Kff::read(std::io::Cursor::new(writer.into_inner()))?,
+                &mut output
+            )?,
+            4
+        );
+
+        let mut reader = Kff::read(std::io::Cursor::new(output))?;
+
+        let kmers: Vec<(Vec<u8>, Vec<u8>)> = reader
+            .next_kmer_section()
+            .unwrap()?
+            .iter()
+            .map(|k| (k.seq(ENCODING), k.data().clone()))
+            .collect();
+        assert_eq!(reader.values().get("ordered"), Some(&1));
+        assert_eq!(
+            kmers,
+            vec![
+                (b"AAC".to_vec(), vec![3]),
+                (b"TCA".to_vec(), vec![2]),
+                (b"GTC".to_vec(), vec![1]),
+            ]
+        );
+
+        let kmers = reader.next_kmer_section().unwrap()?;
+        assert_eq!(reader.values().get("k"), Some(&2));
+        assert_eq!(kmers[0].seq(ENCODING), b"TA");
+
+        assert!(reader.next_kmer_section().is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+//! Split a KFF file in many standalone KFF files
+
+/* std use */
+use std::hash::Hash as _;
+use std::hash::Hasher as _;
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::section::values::AbcValues as _;
+use crate::Kff;
+use crate::Seq2Bit;
+
+/// Strategy used to assign kmer sections to shards
+#[derive(
+    std::clone::Clone, std::marker::Copy, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum Strategy {
+    /// Split hash range of minimizer in this number of shards, Raw sections go in first shard
+    Minimizer(usize),
+
+    /// Maximal number of kmer sections per shard
+    Sections(u64),
+
+    /// Target number of bytes of kmer sections per shard, a shard contains at least one kmer section
+    Size(u64),
+}
+
+/// Split `input` in shards according to `strategy`
+///
+/// `create` is called with index of shard to get writable of each shard. Each shard is a valid KFF
+/// file with header of `input`, Values sections needed by its kmer sections, an index and a
+/// footer. Return writable of each shard.
+pub fn split<R, W, F>(mut input: Kff<R>, strategy: Strategy, mut create: F) -> error::Result<Vec<W>>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+    W: std::io::Write + crate::KffWrite,
+    F: FnMut(usize) -> error::Result<W>,
+{
+    let mut shards: Vec<Shard<W>> = Vec::new();
+
+    if let Strategy::Minimizer(nb_shard) = strategy {
+        for i in 0..nb_shard {
+            shards.push(Shard::new(create(i)?, input.header().clone())?);
+        }
+    }
+
+    while let Some(section) = input.next_block_section() {
+        let section = section?;
+        let max = input.values().get_value("max")?;
+        let size = section.size(max);
+
+        let target = match strategy {
+            Strategy::Minimizer(nb_shard) => section
+                .minimizer()
+                .map(|m| shard_of_minimizer(m, nb_shard))
+                .unwrap_or(0),
+            Strategy::Sections(nb_section) => match shards.last() {
+                Some(shard) if shard.nb_section < nb_section => shards.len() - 1,
+                _ => shards.len(),
+            },
+            Strategy::Size(target_size) => match shards.last() {
+                Some(shard) if shard.size + size <= target_size => shards.len() - 1,
+                _ => shards.len(),
+            },
+        };
+
+        if target == shards.len() {
+            shards.push(Shard::new(create(target)?, input.header().clone())?);
+        }
+
+        shards[target].write(input.values(), section, size)?;
+    }
+
+    let mut output = Vec::with_capacity(shards.len());
+    for mut shard in shards {
+        shard.output.finalize_with_index()?;
+        output.push(shard.output.into_inner());
+    }
+
+    Ok(output)
+}
+
+/// Get shard of a minimizer, hash range is split in `nb_shard` equal parts
+pub fn shard_of_minimizer(minimizer: &Seq2Bit, nb_shard: usize) -> usize {
+    let mut hasher = rustc_hash::FxHasher::default();
+    minimizer.hash(&mut hasher);
+
+    ((hasher.finish() as u128 * nb_shard as u128) >> 64) as usize
+}
+
+struct Shard<W>
+where
+    W: std::io::Write + crate::KffWrite,
+{
+    output: Kff<W>,
+    values: Option<section::Values>,
+    nb_section: u64,
+    size: u64,
+}
+
+impl<W> Shard<W>
+where
+    W: std::io::Write + crate::KffWrite,
+{
+    fn new(outer: W, header: section::Header) -> error::Result<Self> {
+        Ok(Self {
+            output: Kff::write(outer, header)?,
+            values: None,
+            nb_section: 0,
+            size: 0,
+        })
+    }
+
+    fn write(
+        &mut self,
+        values: &section::Values,
+        section: section::KmerSection,
+        size: u64,
+    ) -> error::Result<()> {
+        if self.values.as_ref() != Some(values) {
+            self.output.write_values(values.clone())?;
+            self.values = Some(values.clone());
+        }
+
+        match section {
+            section::KmerSection::Raw(blocks) => {
+                self.output.write_raw(section::Raw::new(values)?, &blocks)?
+            }
+            section::KmerSection::Minimizer(minimizer, blocks) => {
+                self.output
+                    .write_minimizer(section::Minimizer::new(values)?, minimizer, &blocks)?
+            }
+        }
+
+        self.nb_section += 1;
+        self.size += size;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn values(k: u64) -> section::Values {
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), k);
+        values.insert("m".to_string(), 3);
+        values.insert("max".to_string(), 255);
+        values.insert("data_size".to_string(), 1);
+
+        values
+    }
+
+    fn block(seq: &[u8], k: u64, minimizer_offset: usize) -> section::Block {
+        let nb_kmer = seq.len() - k as usize + 1;
+
+        section::Block::new(
+            k,
+            1,
+            Kmer::from_ascii(seq, vec![1; nb_kmer], ENCODING),
+            minimizer_offset,
+        )
+    }
+
+    fn input() -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"split".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let values_5 = values(5);
+        writer.write_values(values_5.clone()).unwrap();
+        writer
+            .write_raw(
+                section::Raw::new(&values_5).unwrap(),
+                &[block(b"ACTGACT", 5, 0), block(b"GGGGG", 5, 0)],
+            )
+            .unwrap();
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values_5).unwrap(),
+                crate::kmer::seq2bits(b"AAA", ENCODING),
+                &[block(b"TGAAACT", 5, 2)],
+            )
+            .unwrap();
+
+        let values_4 = values(4);
+        writer.write_values(values_4.clone()).unwrap();
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values_4).unwrap(),
+                crate::kmer::seq2bits(b"CCC", ENCODING),
+                &[block(b"CCCT", 4, 0), block(b"TCCCG", 4, 1)],
+            )
+            .unwrap();
+        writer.finalize().unwrap();
+
+        writer.inner().clone()
+    }
+
+    fn shard_kmers(shard: Vec<u8>) -> error::Result<(Vec<Vec<u8>>, Vec<u8>)> {
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(shard))?;
+        assert_eq!(reader.header().free_block(), b"split");
+
+        let types = reader
+            .index()
+            .as_ref()
+            .unwrap()
+            .pair()
+            .iter()
+            .map(|(t, _)| *t)
+            .collect();
+
+        let mut kmers = Vec::new();
+        while let Some(section) = reader.next_kmer_section() {
+            kmers.extend(section?.iter().map(|k| k.seq(ENCODING)));
+        }
+
+        Ok((kmers, types))
+    }
+
+    #[test]
+    fn sections() -> error::Result<()> {
+        let shards = split(
+            Kff::read(std::io::Cursor::new(input()))?,
+            Strategy::Sections(2),
+            |_| Ok(Vec::new()),
+        )?;
+
+        assert_eq!(shards.len(), 2);
+
+        let (kmers, types) = shard_kmers(shards[0].clone())?;
+        assert_eq!(types, b"vrm");
+        assert_eq!(
+            kmers,
+            vec![
+                b"ACTGA".to_vec(),
+                b"CTGAC".to_vec(),
+                b"TGACT".to_vec(),
+                b"GGGGG".to_vec(),
+                b"TGAAA".to_vec(),
+                b"GAAAC".to_vec(),
+                b"AAACT".to_vec(),
+            ]
+        );
+
+        let (kmers, types) = shard_kmers(shards[1].clone())?;
+        assert_eq!(types, b"vm");
+        assert_eq!(
+            kmers,
+            vec![b"CCCT".to_vec(), b"TCCC".to_vec(), b"CCCG".to_vec()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn size() -> error::Result<()> {
+        let shards = split(
+            Kff::read(std::io::Cursor::new(input()))?,
+            Strategy::Size(20),
+            |_| Ok(Vec::new()),
+        )?;
+
+        assert_eq!(shards.len(), 3);
+
+        assert_eq!(shard_kmers(shards[0].clone())?.1, b"vr");
+        assert_eq!(shard_kmers(shards[1].clone())?.1, b"vm");
+        assert_eq!(shard_kmers(shards[2].clone())?.1, b"vm");
+
+        Ok(())
+    }
+
+    #[test]
+    fn minimizer() -> error::Result<()> {
+        let aaa = shard_of_minimizer(&crate::kmer::seq2bits(b"AAA", ENCODING), 2);
+        let ccc = shard_of_minimizer(&crate::kmer::seq2bits(b"CCC", ENCODING), 2);
+
+        let shards = split(
+            Kff::read(std::io::Cursor::new(input()))?,
+            Strategy::Minimizer(2),
+            |_| Ok(Vec::new()),
+        )?;
+
+        assert_eq!(shards.len(), 2);
+
+        let mut kmers = [Vec::new(), Vec::new()];
+        kmers[0].extend([
+            b"ACTGA".to_vec(),
+            b"CTGAC".to_vec(),
+            b"TGACT".to_vec(),
+            b"GGGGG".to_vec(),
+        ]);
+        kmers[aaa].extend([b"TGAAA".to_vec(), b"GAAAC".to_vec(), b"AAACT".to_vec()]);
+        kmers[ccc].extend([b"CCCT".to_vec(), b"TCCC".to_vec(), b"CCCG".to_vec()]);
+
+        assert_eq!(shard_kmers(shards[0].clone())?.0, kmers[0]);
+        assert_eq!(shard_kmers(shards[1].clone())?.0, kmers[1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! Compute statistics of a KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::section::values::AbcValues as _;
+use crate::Kff;
+
+/// Statistics of a kmer section
+#[derive(
+    getset::Getters, std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get = "pub")]
+pub struct SectionStats {
+    /// Type of section, 'r' for Raw or 'm' for Minimizer
+    section_type: char,
+
+    /// Size of kmer
+    k: u64,
+
+    /// Number of block
+    nb_block: u64,
+
+    /// Number of kmer
+    nb_kmer: u64,
+
+    /// Number of bytes used by section
+    size: u64,
+}
+
+/// Statistics of a KFF file
+#[derive(
+    getset::Getters,
+    std::clone::Clone,
+    std::fmt::Debug,
+    std::default::Default,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get = "pub")]
+pub struct Stats {
+    /// Name of each declared variable associate to all distinct values, footer isn't include
+    values: std::collections::BTreeMap<String, Vec<u64>>,
+
+    /// Variables of footer, the Values section after last kmer section
+    footer: std::collections::BTreeMap<String, u64>,
+
+    /// Statistics of each kmer section
+    sections: Vec<SectionStats>,
+
+    /// Number of kmer
+    nb_kmer: u64,
+
+    /// Number of block
+    nb_block: u64,
+
+    /// Number of block for each number of kmer per block
+    block_length: std::collections::BTreeMap<u64, u64>,
+
+    /// Number of kmer for each data value, data are read as big endian integer, data larger than 8 bytes are ignored
+    data: std::collections::BTreeMap<u64, u64>,
+
+    /// Number of bytes used to store minimizers of Minimizer sections
+    minimizer_bytes: u64,
+
+    /// Number of bytes used to store kmer sequences
+    kmer_bytes: u64,
+
+    /// Number of bytes used to store data
+    data_bytes: u64,
+}
+
+impl<R> Kff<R>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+{
+    /// Read all remaining sections and compute statistics
+    pub fn stats(&mut self) -> error::Result<Stats> {
+        let mut stats = Stats::default();
+        let mut declared = self.values().clone();
+
+        loop {
+            let section = self.next_block_section();
+
+            match section {
+                Some(section) => {
+                    stats.add_values(self.values());
+                    stats.add_section(self.values(), &section?)?;
+                    declared.clone_from(self.values());
+                }
+                None => {
+                    if self.values() != &declared {
+                        stats.footer = self
+                            .values()
+                            .iter()
+                            .map(|(key, value)| (key.clone(), *value))
+                            .collect();
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+impl Stats {
+    fn add_values(&mut self, values: &section::Values) {
+        for (key, value) in values {
+            let seen = self.values.entry(key.clone()).or_default();
+            if !seen.contains(value) {
+                seen.push(*value);
+            }
+        }
+    }
+
+    fn add_section(
+        &mut self,
+        values: &section::Values,
+        section: &section::KmerSection,
+    ) -> error::Result<()> {
+        let k = values.get_value("k")?;
+        let max = values.get_value("max")?;
+
+        let m = section.minimizer().map(|m| (m.len() / 2) as u64);
+        if let Some(m) = m {
+            self.minimizer_bytes += crate::bytes2store_k(m);
+        }
+
+        let mut nb_kmer = 0;
+        for block in section.blocks() {
+            let block_kmer = block.nb_kmer();
+            nb_kmer += block_kmer;
+
+            *self.block_length.entry(block_kmer).or_default() += 1;
+
+            let nuc = (block.kmer().seq2bit().len() / 2) as u64;
+            self.kmer_bytes += crate::bytes2store_k(nuc - m.unwrap_or(0));
+            self.data_bytes += block.kmer().data().len() as u64;
+
+            if *block.data_size() != 0 && *block.data_size() <= 8 {
+                for data in block.kmer().data().chunks(*block.data_size()) {
+                    let value = data.iter().fold(0u64, |acc, x| (acc << 8) | *x as u64);
+                    *self.data.entry(value).or_default() += 1;
+                }
+            }
+        }
+
+        self.nb_kmer += nb_kmer;
+        self.nb_block += section.blocks().len() as u64;
+        self.sections.push(SectionStats {
+            section_type: if m.is_some() { 'm' } else { 'r' },
+            k,
+            nb_block: section.blocks().len() as u64,
+            nb_kmer,
+            size: section.size(max),
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    #[test]
+    fn stats() -> error::Result<()> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"".to_vec())?;
+        let mut writer = Kff::write(Vec::new(), header)?;
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 5);
+        values.insert("m".to_string(), 3);
+        values.insert("max".to_string(), 255);
+        values.insert("data_size".to_string(), 2);
+        writer.write_values(values.clone())?;
+
+        writer.write_raw(
+            section::Raw::new(&values)?,
+            &[
+                section::Block::new(
+                    5,
+                    2,
+                    Kmer::from_ascii(b"ACTGACT", vec![0, 1, 0, 2, 1, 0], ENCODING),
+                    0,
+                ),
+                section::Block::new(5, 2, Kmer::from_ascii(b"GGGGG", vec![0, 1], ENCODING), 0),
+            ],
+        )?;
+
+        values.insert("k".to_string(), 4);
+        writer.write_values(values.clone())?;
+        writer.write_minimizer(
+            section::Minimizer::new(&values)?,
+            crate::kmer::seq2bits(b"AAA", ENCODING),
+            &[section::Block::new(
+                4,
+                2,
+                Kmer::from_ascii(b"TAAAC", vec![0, 1, 0, 1], ENCODING),
+                1,
+            )],
+        )?;
+        writer.finalize_with_index()?;
+
+        let mut reader = Kff::read(std::io::Cursor::new(writer.into_inner()))?;
+        let stats = reader.stats()?;
+
+        assert_eq!(stats.nb_kmer(), &6);
+        assert_eq!(stats.nb_block(), &3);
+        assert_eq!(
+            stats.block_length(),
+            &std::collections::BTreeMap::from([(1, 1), (2, 1), (3, 1)])
+        );
+        assert_eq!(
+            stats.data(),
+            &std::collections::BTreeMap::from([(1, 4), (2, 1), (256, 1)])
+        );
+        assert_eq!(stats.minimizer_bytes(), &1);
+        assert_eq!(stats.kmer_bytes(), &5);
+        assert_eq!(stats.data_bytes(), &12);
+
+        assert_eq!(stats.values().get("k"), Some(&vec![5, 4]));
+        assert_eq!(stats.values().get("max"), Some(&vec![255]));
+        assert!(!stats.values().contains_key("first_index"));
+        assert!(!stats.values().contains_key("footer_size"));
+        assert_eq!(
+            stats.footer().keys().collect::<Vec<_>>(),
+            vec!["first_index", "footer_size"]
+        );
+
+        assert_eq!(
+            stats.sections(),
+            &vec![
+                SectionStats {
+                    section_type: 'r',
+                    k: 5,
+                    nb_block: 2,
+                    nb_kmer: 4,
+                    size: 23,
+                },
+                SectionStats {
+                    section_type: 'm',
+                    k: 4,
+                    nb_block: 1,
+                    nb_kmer: 2,
+                    size: 18,
+                }
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+//! Convert KFF file from and to text dump of kmer counters
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::Kff;
+use crate::KffWrite as _;
+use crate::Kmer;
+
+use crate::section::values::AbcValues as _;
+
+/// Maximal number of kmer in a Raw section write by [from_text]
+pub const KMER_PER_SECTION: usize = 1 << 16;
+
+/// Text format of kmer and count
+#[derive(
+    std::clone::Clone, std::marker::Copy, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum Format {
+    /// One `KMER<tab>count` per line, output of `kmc_dump` and `jellyfish dump -c -t`
+    Tabulated,
+
+    /// One `KMER count` per line, output of `jellyfish dump -c`
+    Column,
+
+    /// A `>count` line followed by a `KMER` line, default output of `jellyfish dump`
+    Fasta,
+}
+
+/// Write each kmer of `input` in `outer` in `format`, return number of kmer write
+///
+/// Data are written as big endian unsigned integer if `data_size` is lower or equal to 8, in
+/// hexadecimal otherwise. If `data_size` is 0 only kmer is written.
+pub fn to_text<R, W>(mut input: Kff<R>, mut outer: W, format: Format) -> error::Result<u64>
+where
+    R: std::io::Read + std::io::BufRead + crate::KffRead,
+    W: std::io::Write,
+{
+    let encoding = *input.header().encoding();
+    let mut nb_kmer = 0;
+
+    while let Some(section) = input.next_kmer_section() {
+        for kmer in section? {
+            match format {
+                Format::Tabulated | Format::Column => {
+                    outer.write_bytes(&kmer.seq(encoding))?;
+                    if !kmer.data().is_empty() {
+                        let separator = if format == Format::Tabulated {
+                            '\t'
+                        } else {
+                            ' '
+                        };
+                        write!(outer, "{}{}", separator, data2string(kmer.data()))?;
+                    }
+                }
+                Format::Fasta => {
+                    writeln!(outer, ">{}", data2string(kmer.data()))?;
+                    outer.write_bytes(&kmer.seq(encoding))?;
+                }
+            }
+            outer.write_bytes(b"\n")?;
+
+            nb_kmer += 1;
+        }
+    }
+
+    outer.flush()?;
+
+    Ok(nb_kmer)
+}
+
+/// Read kmers and counts in `format` from `input` and write kmers in a KFF file, return number of kmer write
+///
+/// In [Format::Tabulated] and [Format::Column] kmer and count could be separate by spaces,
+/// tabulations or a comma. Count is written in `data_size` bytes as big endian, a count larger
+/// than `data_size` bytes is an error, if `data_size` is 0 counts are ignored. If `data_size` is
+/// larger than 8, a count of exactly `2 * data_size` hexadecimal digits, as written by [to_text],
+/// is read as hexadecimal data. Empty lines are
+/// ignored and all kmers must have the same size. Input is read line by line, kmers are written
+/// one per block in Raw sections of at most [KMER_PER_SECTION] kmers, an index and a footer are
+/// written at end.
+pub fn from_text<R, W>(
+    input: R,
+    outer: W,
+    header: section::Header,
+    format: Format,
+    data_size: usize,
+) -> error::Result<u64>
+where
+    R: std::io::BufRead,
+    W: std::io::Write + crate::KffWrite,
+{
+    let encoding = *header.encoding();
+    let mut output = Kff::write(outer, header)?;
+
+    let mut values: Option<section::Values> = None;
+    let mut blocks = Vec::with_capacity(KMER_PER_SECTION);
+    let mut nb_kmer = 0;
+
+    let mut lines = Lines::new(input);
+    while let Some(line) = lines.next_line()? {
+        let (seq, count) = match format {
+            Format::Tabulated | Format::Column => {
+                let mut fields = line
+                    .split(|c: char| c.is_ascii_whitespace() || c == ',')
+                    .filter(|field| !field.is_empty());
+
+                let seq = fields.next().unwrap_or_default().to_string();
+                (seq, fields.next().map(|count| count.to_string()))
+            }
+            Format::Fasta => {
+                let count = match line.strip_prefix('>') {
+                    Some(count) => count.trim().to_string(),
+                    None => return Err(lines.error("header must begin with '>'")),
+                };
+
+                match lines.next_line()? {
+                    Some(seq) => (seq.trim().to_string(), Some(count)),
+                    None => return Err(lines.error("kmer is missing")),
+                }
+            }
+        };
+        let seq = seq.as_bytes();
+
+        if let Some(nuc) = seq.iter().find(|n| !b"ACGTacgt".contains(n)) {
+            return Err(lines.error(&format!("'{}' isn't a nucleotide", *nuc as char)));
+        }
+
+        let values = values.get_or_insert_with(|| {
+            let mut values = section::Values::new();
+            values.insert("k".to_string(), seq.len() as u64);
+            values.insert("max".to_string(), 1);
+            values.insert("data_size".to_string(), data_size as u64);
+            values.insert("ordered".to_string(), false as u64);
+
+            values
+        });
+
+        let k = values["k"];
+        if seq.len() as u64 != k {
+            return Err(lines.error(&format!("kmer length {} isn't {}", seq.len(), k)));
+        }
+
+        let data = parse_data(count.as_deref(), data_size).map_err(|msg| lines.error(&msg))?;
+
+        blocks.push(section::Block::new(
+            k,
+            data_size,
+            Kmer::from_ascii(seq, data, encoding),
+            0,
+        ));
+
+        if blocks.len() == KMER_PER_SECTION {
+            write_blocks(&mut output, values, &mut blocks)?;
+        }
+
+        nb_kmer += 1;
+    }
+
+    if let Some(values) = values.as_ref() {
+        write_blocks(&mut output, values, &mut blocks)?;
+    }
+
+    output.finalize_with_index()?;
+
+    Ok(nb_kmer)
+}
+
+/// Read non empty lines and keep track of line number
+struct Lines<R>
+where
+    R: std::io::BufRead,
+{
+    inner: std::io::Lines<R>,
+    line_number: u64,
+}
+
+impl<R> Lines<R>
+where
+    R: std::io::BufRead,
+{
+    fn new(inner: R) -> Self {
+        Self {
+            inner: inner.lines(),
+            line_number: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> error::Result<Option<String>> {
+        for line in self.inner.by_ref() {
+            let line = line?;
+            self.line_number += 1;
+
+            if !line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn error(&self, msg: &str) -> error::Error {
+        error::Kff::BadTextLine(self.line_number, msg.to_string()).into()
+    }
+}
+
+/// Format data as big endian unsigned integer if it's lower or equal to 8 bytes, in hexadecimal otherwise, empty data give an empty string
+pub(crate) fn data2string(data: &[u8]) -> String {
+    match data.len() {
+        0 => String::new(),
+        1..=8 => data
+            .iter()
+            .fold(0u64, |acc, x| (acc << 8) | *x as u64)
+            .to_string(),
+        _ => data.iter().map(|x| format!("{:02x}", x)).collect(),
+    }
+}
+
+fn parse_data(field: Option<&str>, data_size: usize) -> std::result::Result<Vec<u8>, String> {
+    if data_size == 0 {
+        return Ok(Vec::new());
+    }
+
+    let field = field.ok_or_else(|| "data is missing".to_string())?;
+
+    // Data larger than 8 bytes written by to_text
+    if data_size > 8 && field.len() == data_size * 2 && field.bytes().all(|c| c.is_ascii_hexdigit())
+    {
+        return Ok((0..data_size)
+            .map(|i| u8::from_str_radix(&field[i * 2..i * 2 + 2], 16).unwrap_or_default())
+            .collect());
+    }
+
+    let value = field
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' isn't an unsigned integer", field))?;
+
+    if data_size < 8 && value >> (data_size * 8) != 0 {
+        return Err(format!("{} can't be stored in {} bytes", value, data_size));
+    }
+
+    let mut data = vec![0; data_size.saturating_sub(8)];
+    data.extend_from_slice(&value.to_be_bytes()[8usize.saturating_sub(data_size)..]);
+
+    Ok(data)
+}
+
+fn write_blocks<W>(
+    output: &mut Kff<W>,
+    values: &section::Values,
+    blocks: &mut Vec<section::Block>,
+) -> error::Result<()>
+where
+    W: std::io::Write + crate::KffWrite,
+{
+    if output.values() != values {
+        output.write_values(values.clone())?;
+    }
+
+    if !blocks.is_empty() {
+        output.write_raw(section::Raw::new(values)?, blocks)?;
+        blocks.clear();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn header() -> section::Header {
+        section::Header::new(1, 0, ENCODING, true, false, b"".to_vec()).unwrap()
+    }
+
+    fn convert(input: &[u8], format: Format, data_size: usize) -> error::Result<Vec<u8>> {
+        let mut kff = Vec::new();
+        from_text(input, &mut kff, header(), format, data_size)?;
+
+        Ok(kff)
+    }
+
+    fn text(kff: Vec<u8>, format: Format) -> error::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        to_text(Kff::read(std::io::Cursor::new(kff))?, &mut output, format)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn round_trip() -> error::Result<()> {
+        let input = b"ACTGA\t3\nctgac 258\n\nTTTTT,0\n";
+
+        let mut kff = Vec::new();
+        assert_eq!(
+            from_text(&input[..], &mut kff, header(), Format::Tabulated, 2)?,
+            3
+        );
+
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(kff.clone()))?;
+        let kmers = reader.next_kmer_section().unwrap()?;
+        assert_eq!(reader.values().get("k"), Some(&5));
+        assert_eq!(reader.values().get("max"), Some(&1));
+        assert_eq!(kmers[1].seq(ENCODING), b"CTGAC");
+        assert_eq!(kmers[1].data(), &vec![1, 2]);
+
+        let mut output = Vec::new();
+        assert_eq!(
+            to_text(
+                Kff::read(std::io::Cursor::new(kff.clone()))?,
+                &mut output,
+                Format::Tabulated
+            )?,
+            3
+        );
+        assert_eq!(output, b"ACTGA\t3\nCTGAC\t258\nTTTTT\t0\n");
+
+        assert_eq!(
+            text(kff.clone(), Format::Column)?,
+            b"ACTGA 3\nCTGAC 258\nTTTTT 0\n"
+        );
+        assert_eq!(
+            text(kff, Format::Fasta)?,
+            b">3\nACTGA\n>258\nCTGAC\n>0\nTTTTT\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn jellyfish_fasta() -> error::Result<()> {
+        let input = b">70000\nACTGA\n\n>2\r\nGGGGG\r\n";
+
+        let kff = convert(input, Format::Fasta, 4)?;
+        assert_eq!(text(kff, Format::Tabulated)?, b"ACTGA\t70000\nGGGGG\t2\n");
+
+        assert!(matches!(
+            convert(b">1\nACTGA\n>2\n", Format::Fasta, 1),
+            Err(error::Error::Kff(error::Kff::BadTextLine(3, _)))
+        ));
+        assert!(matches!(
+            convert(b"ACTGA\n", Format::Fasta, 1),
+            Err(error::Error::Kff(error::Kff::BadTextLine(1, _)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_data() -> error::Result<()> {
+        let kff = convert(b"AC 1\n", Format::Column, 9)?;
+        assert_eq!(text(kff, Format::Tabulated)?, b"AC\t000000000000000001\n");
+
+        // Hexadecimal data write by to_text is read back
+        let dump = b"AC\t0102030405060708ff\nGT\t000000000000000001\n";
+        let kff = convert(dump, Format::Tabulated, 9)?;
+        let kmers: Vec<Kmer> = Kff::read(std::io::Cursor::new(kff.clone()))?
+            .kmers()
+            .collect::<error::Result<Vec<_>>>()?;
+        assert_eq!(kmers[0].data(), &vec![1, 2, 3, 4, 5, 6, 7, 8, 0xff]);
+        assert_eq!(text(kff, Format::Tabulated)?, dump);
+
+        let kff = convert(b"AC\nGT\n", Format::Column, 0)?;
+        assert_eq!(text(kff.clone(), Format::Tabulated)?, b"AC\nGT\n");
+        assert_eq!(text(kff, Format::Fasta)?, b">\nAC\n>\nGT\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_line() -> error::Result<()> {
+        assert!(matches!(
+            convert(b"ACTGA 1\nACNGA 1\n", Format::Column, 1),
+            Err(error::Error::Kff(error::Kff::BadTextLine(2, _)))
+        ));
+        assert!(matches!(
+            convert(b"ACTGA 1\n\nACTG 1\n", Format::Column, 1),
+            Err(error::Error::Kff(error::Kff::BadTextLine(3, _)))
+        ));
+        assert!(matches!(
+            convert(b"ACTGA 256\n", Format::Column, 1),
+            Err(error::Error::Kff(error::Kff::BadTextLine(1, _)))
+        ));
+        assert!(matches!(
+            convert(b"ACTGA\n", Format::Column, 1),
+            Err(error::Error::Kff(error::Kff::BadTextLine(1, _)))
+        ));
+
+        Ok(())
+    }
+}
@@ -7,14 +7,22 @@
 /* project use */
 
 /* mod declaration */
+pub mod append;
+pub mod checksum;
 pub mod global_index;
 pub mod kmer;
+pub mod kmer_index;
+pub mod limits;
 pub mod read;
 pub mod write;
 
 /* pub use */
+pub use append::AppendFile;
+pub use checksum::Crc32c;
 pub use global_index::GlobalIndex;
 pub use kmer::{Data, Kmer, Seq2Bit};
+pub use kmer_index::KmerIndex;
+pub use limits::ReadLimits;
 pub use read::KffRead;
 pub use write::KffWrite;
 
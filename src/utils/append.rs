@@ -0,0 +1,128 @@
+//! Write new sections at end of an existing KFF file
+
+/* std use */
+use std::io::Read as _;
+use std::io::Seek as _;
+use std::io::Write as _;
+
+/* crate use */
+
+/* project use */
+
+/// A file writer that start at footer of an existing file and can undo writes until commit
+///
+/// Bytes between start position and end of file, footer and final signature, are kept in memory
+/// and overwritten by new bytes. [commit](AppendFile::commit), call by
+/// [Kff::finalize](crate::Kff::finalize), truncate file after last byte written. If writer is
+/// dropped before commit, new bytes are removed and old tail is written back, so file is
+/// unchanged. A crash before commit or drop still leave file without final signature.
+pub struct AppendFile {
+    inner: std::io::BufWriter<std::fs::File>,
+
+    /// Start position and bytes that was after it, None after commit
+    tail: Option<(u64, Vec<u8>)>,
+}
+
+impl AppendFile {
+    /// Keep bytes of `file` after `position` and prepare to write at `position`
+    pub fn new(mut file: std::fs::File, position: u64) -> std::io::Result<Self> {
+        file.seek(std::io::SeekFrom::Start(position))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        file.seek(std::io::SeekFrom::Start(position))?;
+
+        Ok(Self {
+            inner: std::io::BufWriter::new(file),
+            tail: Some((position, tail)),
+        })
+    }
+
+    /// Flush bytes written, remove everything after them and forget old tail
+    pub fn commit(&mut self) -> std::io::Result<()> {
+        self.inner.flush()?;
+        let end = self.inner.get_mut().stream_position()?;
+        self.inner.get_ref().set_len(end)?;
+        self.tail = None;
+
+        Ok(())
+    }
+
+    /// Remove bytes written and write old tail back
+    fn restore(&mut self, position: u64, tail: &[u8]) -> std::io::Result<()> {
+        self.inner.flush()?;
+
+        let file = self.inner.get_mut();
+        file.set_len(position)?;
+        file.seek(std::io::SeekFrom::Start(position))?;
+        file.write_all(tail)?;
+        file.flush()
+    }
+}
+
+impl std::io::Write for AppendFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl std::ops::Drop for AppendFile {
+    fn drop(&mut self) {
+        if let Some((position, tail)) = self.tail.take() {
+            // Error can't be returned by drop, file is left as it is
+            let _ = self.restore(position, &tail);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::error;
+
+    fn append(
+        content: &[u8],
+        position: u64,
+    ) -> error::Result<(tempfile::NamedTempFile, AppendFile)> {
+        let file = tempfile::NamedTempFile::new()?;
+        std::fs::write(file.path(), content)?;
+
+        let writer = AppendFile::new(
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(file.path())?,
+            position,
+        )?;
+
+        Ok((file, writer))
+    }
+
+    #[test]
+    fn commit() -> error::Result<()> {
+        let (file, mut writer) = append(b"abcdefgh", 3)?;
+        writer.write_all(b"XY")?;
+        writer.commit()?;
+        drop(writer);
+
+        assert_eq!(std::fs::read(file.path())?, b"abcXY");
+
+        Ok(())
+    }
+
+    #[test]
+    fn restore() -> error::Result<()> {
+        let (file, mut writer) = append(b"abcdefgh", 3)?;
+        writer.write_all(&[b'X'; 100])?;
+        writer.flush()?;
+        drop(writer);
+
+        assert_eq!(std::fs::read(file.path())?, b"abcdefgh");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! CRC32C checksum of KFF files
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::utils::read::Pending;
+
+/// Name of footer variable that store checksum
+pub const FOOTER_NAME: &str = "crc32c";
+
+/// Reversed Castagnoli polynomial
+const POLYNOMIAL: u32 = 0x82F6_3B78;
+
+/// Lookup table of CRC32C for each byte
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+/// Incremental CRC32C (Castagnoli) of a stream of bytes
+#[derive(
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::default::Default,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+)]
+pub struct Crc32c {
+    state: u32,
+}
+
+impl Crc32c {
+    /// Create a checksum of zero bytes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add bytes to checksum
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut crc = !self.state;
+        for byte in bytes {
+            crc = TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+        self.state = !crc;
+    }
+
+    /// Checksum of bytes add until now
+    pub fn value(&self) -> u32 {
+        self.state
+    }
+}
+
+/// Compute CRC32C of bytes
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    let mut checksum = Crc32c::new();
+    checksum.update(bytes);
+
+    checksum.value()
+}
+
+/// Compute CRC32C of next `len` bytes of a reader
+pub(crate) fn crc32c_of<R>(inner: &mut R, len: u64) -> std::io::Result<Crc32c>
+where
+    R: std::io::Read,
+{
+    let mut checksum = Crc32c::new();
+    let mut reader = std::io::Read::take(inner, len);
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let read = std::io::Read::read(&mut reader, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        checksum.update(&buffer[..read]);
+    }
+
+    if reader.limit() != 0 {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(checksum)
+}
+
+/// A reader that compute checksum of all bytes read in inner reader
+pub(crate) struct Hasher<'a, R> {
+    inner: &'a mut R,
+    checksum: Crc32c,
+    pending: Pending,
+}
+
+impl<'a, R> Hasher<'a, R>
+where
+    R: std::io::BufRead,
+{
+    /// Wrap a reader, checksum start from `checksum`
+    pub(crate) fn new(inner: &'a mut R, checksum: Crc32c) -> Self {
+        Self {
+            inner,
+            checksum,
+            pending: Pending::default(),
+        }
+    }
+
+    /// Checksum of bytes read until now
+    pub(crate) fn checksum(&self) -> Crc32c {
+        self.checksum
+    }
+}
+
+impl<'a, R> std::io::Read for Hasher<'a, R>
+where
+    R: std::io::BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.pending.consume(len);
+        self.checksum.update(&buf[..len]);
+
+        Ok(len)
+    }
+}
+
+impl<'a, R> std::io::BufRead for Hasher<'a, R>
+where
+    R: std::io::BufRead,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        let buf = self.inner.fill_buf()?;
+        self.pending.fill(buf);
+
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.checksum.update(self.pending.consume(amt));
+        self.inner.consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::error;
+    use crate::KffRead as _;
+
+    #[test]
+    fn crc32c_() {
+        assert_eq!(crc32c(b""), 0);
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+        assert_eq!(crc32c(&[0; 32]), 0x8A91_36AA);
+
+        let mut checksum = Crc32c::new();
+        checksum.update(b"1234");
+        checksum.update(b"56789");
+        assert_eq!(checksum.value(), 0xE306_9283);
+    }
+
+    #[test]
+    fn crc32c_of_() -> error::Result<()> {
+        let mut inner = std::io::Cursor::new(b"123456789abc".to_vec());
+        assert_eq!(crc32c_of(&mut inner, 9)?.value(), 0xE306_9283);
+        assert!(crc32c_of(&mut inner, 4).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn hasher() -> error::Result<()> {
+        let mut inner = std::io::Cursor::new(b"123456789".to_vec());
+
+        let mut start = Crc32c::new();
+        start.update(b"12");
+        inner.skip_n_bytes(2)?;
+
+        let mut hasher = Hasher::new(&mut inner, start);
+        hasher.read_n_bytes::<3>()?;
+        hasher.read_n_bytes_dyn(4)?;
+
+        assert_eq!(hasher.checksum().value(), 0xE306_9283);
+
+        // Small buffer, bytes are consumed across many fill_buf
+        let mut inner = std::io::BufReader::with_capacity(4, &b"1234\0abc\0de"[..]);
+        let mut hasher = Hasher::new(&mut inner, Crc32c::new());
+        hasher.read_ascii()?;
+        hasher.read_u8()?;
+        hasher.read_ascii()?;
+        hasher.skip_n_bytes(2)?;
+
+        let mut expected = Crc32c::new();
+        expected.update(b"1234\0abc\0de");
+        assert_eq!(hasher.checksum(), expected);
+
+        Ok(())
+    }
+}
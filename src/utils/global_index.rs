@@ -8,6 +8,9 @@
 use crate::error;
 use crate::section;
 use crate::KffRead;
+use crate::Seq2Bit;
+
+use crate::section::values::AbcValues as _;
 
 /// Struct that manage and build a global index of a Kff file
 #[derive(getset::Getters, getset::Setters, getset::MutGetters, std::default::Default)]
@@ -22,10 +25,25 @@ impl GlobalIndex {
     ///
     /// At the end of scan position in file is the start of the first_index
     pub fn new<R>(inner: &mut R, first_index: u64) -> error::Result<Self>
+    where
+        R: std::io::Read + std::io::Seek + KffRead,
+    {
+        Self::new_with_limits(inner, first_index, &crate::ReadLimits::default())
+    }
+
+    /// Same as [GlobalIndex::new] but each index is read with `limits`
+    ///
+    /// A chain of index that come back to an index already read is an error.
+    pub fn new_with_limits<R>(
+        inner: &mut R,
+        first_index: u64,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<Self>
     where
         R: std::io::Read + std::io::Seek + KffRead,
     {
         let mut pair = Vec::new();
+        let mut visited = rustc_hash::FxHashSet::default();
 
         inner.seek(std::io::SeekFrom::Start(first_index))?;
 
@@ -35,7 +53,12 @@ impl GlobalIndex {
         }
 
         loop {
-            let local_index = section::Index::read(inner)?;
+            let position = inner.stream_position()?;
+            if !visited.insert(position) {
+                return Err(error::Kff::IndexLoop(position).into());
+            }
+
+            let local_index = section::Index::read_with_limits(inner, limits)?;
 
             let relative_to = inner.stream_position()?;
 
@@ -59,6 +82,46 @@ impl GlobalIndex {
 
         Ok(Self { pair })
     }
+
+    /// Build a map from minimizer to position in [GlobalIndex::pair] of Minimizer sections
+    ///
+    /// Only values sections and minimizer of Minimizer sections are read, with `limits`.
+    pub fn minimizers<R>(
+        &self,
+        inner: &mut R,
+        limits: &crate::ReadLimits,
+    ) -> error::Result<rustc_hash::FxHashMap<Seq2Bit, Vec<usize>>>
+    where
+        R: std::io::Read + std::io::Seek + KffRead,
+    {
+        let mut minimizers: rustc_hash::FxHashMap<Seq2Bit, Vec<usize>> =
+            rustc_hash::FxHashMap::default();
+        let mut values = None;
+
+        for (n, (section_type, position)) in self.pair.iter().enumerate() {
+            match section_type {
+                b'v' => {
+                    inner.seek(std::io::SeekFrom::Start(position + 1))?;
+                    values = Some(section::Values::read_with_limits(inner, limits)?);
+                }
+                b'm' => {
+                    let values = values
+                        .as_ref()
+                        .ok_or(error::Kff::NoValueSectionBeforeTarget)?;
+                    let mut section = section::Minimizer::new(values)?;
+                    section.set_limits(*limits);
+
+                    inner.seek(std::io::SeekFrom::Start(position + 1))?;
+                    let minimizer = section.read_minimizer(inner)?;
+
+                    minimizers.entry(minimizer).or_default().push(n);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(minimizers)
+    }
 }
 
 #[cfg(test)]
@@ -108,4 +171,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn limits() -> error::Result<()> {
+        let mut file = std::io::Cursor::new(vec![
+            b'i', // Index start
+            0, 0, 0, 0, 0, 0, 0, 2, // Number of value indexed
+            b'v', 0, 0, 0, 0, 0, 0, 0, 1, // Values section
+            b'r', 0, 0, 0, 0, 0, 0, 0, 1, // Raw section
+            0, 0, 0, 0, 0, 0, 0, 0, // Next index section
+        ]);
+
+        let mut limits = crate::ReadLimits::default();
+        limits.set_max_section_size(17);
+        assert!(matches!(
+            GlobalIndex::new_with_limits(&mut file, 0, &limits),
+            Err(error::Error::Kff(error::Kff::LimitExceeded(_, 18, 17)))
+        ));
+        limits.set_max_section_size(18);
+        assert_eq!(
+            GlobalIndex::new_with_limits(&mut file, 0, &limits)?
+                .pair()
+                .len(),
+            2
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn index_loop() -> error::Result<()> {
+        let mut file = std::io::Cursor::new(vec![
+            b'i', // Index start
+            0, 0, 0, 0, 0, 0, 0, 0, // Number of value indexed
+            255, 255, 255, 255, 255, 255, 255, 240, // Next index is this index
+        ]);
+
+        assert!(matches!(
+            GlobalIndex::new(&mut file, 0),
+            Err(error::Error::Kff(error::Kff::IndexLoop(1)))
+        ));
+
+        Ok(())
+    }
 }
@@ -5,6 +5,7 @@
 /* crate use */
 
 /* project use */
+use crate::error;
 
 /// Represent a sequence in 2 bit
 pub type Seq2Bit = bitvec::boxed::BitBox<u8, bitvec::order::Msb0>;
@@ -17,6 +18,7 @@ pub type Data = Vec<u8>;
     getset::Getters,
     getset::Setters,
     getset::MutGetters,
+    std::clone::Clone,
     std::fmt::Debug,
     std::cmp::PartialEq,
     std::cmp::Eq,
@@ -115,6 +117,93 @@ pub fn bits2seq(bits: &Seq2Bit, encoding: u8) -> Vec<u8> {
     ret
 }
 
+/// Compute reverse complement of a Seq2Bit
+pub fn rev_comp(bits: &Seq2Bit, encoding: u8) -> Seq2Bit {
+    let rev_encoding = rev_encoding(encoding);
+
+    // Complement switch second bit of internal encoding
+    let complement: Vec<u8> = (0..4)
+        .map(|code| {
+            let internal = nuc2internal(encoding2nuc(code << 6, rev_encoding)) ^ 0b10;
+            nuc2encoding(internal2nuc(internal), encoding) >> 6
+        })
+        .collect();
+
+    let mut ret = bitvec::vec::BitVec::with_capacity(bits.len());
+    for bit in bits.rchunks_exact(2) {
+        let code = complement[((bit[0] as usize) << 1) | bit[1] as usize];
+
+        ret.push(code & 0b10 != 0);
+        ret.push(code & 0b01 != 0);
+    }
+
+    ret.into_boxed_bitslice()
+}
+
+/// Get minimum of a Seq2Bit and its reverse complement
+pub fn canonical(bits: &Seq2Bit, encoding: u8) -> Seq2Bit {
+    std::cmp::min(bits.clone(), rev_comp(bits, encoding))
+}
+
+/// Hash 2 bits representation of a kmer, or of its canonical form, stable between platforms and runs
+pub(crate) fn hash(kmer: &Seq2Bit, encoding: u8, canonical: bool) -> u64 {
+    use bitvec::field::BitField as _;
+
+    let canonical_kmer;
+    let kmer = if canonical {
+        canonical_kmer = crate::kmer::canonical(kmer, encoding);
+        &canonical_kmer
+    } else {
+        kmer
+    };
+
+    let mut hash = kmer.len() as u64;
+    for chunk in kmer.chunks(64) {
+        hash = splitmix64(hash ^ chunk.load_be::<u64>());
+    }
+
+    hash
+}
+
+pub(crate) fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    x ^ (x >> 31)
+}
+
+/// Find offset of smallest m-mer, by 2 bits representation, of kmer and get a copy of it
+///
+/// If many m-mers are the smallest, the first one is selected. If `m` is larger than size of kmer
+/// an error is returned.
+pub fn minimizer(
+    kmer: &bitvec::slice::BitSlice<u8, bitvec::order::Msb0>,
+    m: usize,
+) -> error::Result<(usize, Seq2Bit)> {
+    let offset = minimizer_offset(kmer, m)?;
+
+    let mut minimizer = bitvec::vec::BitVec::with_capacity(m * 2);
+    minimizer.extend_from_bitslice(&kmer[offset * 2..(offset + m) * 2]);
+
+    Ok((offset, minimizer.into_boxed_bitslice()))
+}
+
+/// Same as [minimizer] without copy of minimizer
+pub(crate) fn minimizer_offset(
+    kmer: &bitvec::slice::BitSlice<u8, bitvec::order::Msb0>,
+    m: usize,
+) -> error::Result<usize> {
+    let nb_nuc = kmer.len() / 2;
+    if m > nb_nuc {
+        return Err(error::Kff::MinimizerSizeTooLarge(m as u64, nb_nuc as u64).into());
+    }
+
+    Ok((0..=(nb_nuc - m))
+        .min_by(|a, b| kmer[a * 2..(a + m) * 2].cmp(&kmer[b * 2..(b + m) * 2]))
+        .unwrap_or(0))
+}
+
 /// Convert an encoding in reverse version
 #[inline]
 fn rev_encoding(encoding: u8) -> u8 {
@@ -320,4 +409,45 @@ mod tests {
 
         assert_eq!(rev_encoding(0b01110010), 0b10001101);
     }
+
+    #[test]
+    fn minimizer_() -> error::Result<()> {
+        let encoding = 0b00011011;
+
+        let (offset, mini) = minimizer(&seq2bits(b"TGACA", encoding), 2)?;
+        assert_eq!(offset, 2);
+        assert_eq!(mini, seq2bits(b"AC", encoding));
+
+        // first smallest m-mer is selected
+        let (offset, mini) = minimizer(&seq2bits(b"GAAGAA", encoding), 2)?;
+        assert_eq!(offset, 1);
+        assert_eq!(mini, seq2bits(b"AA", encoding));
+
+        let (offset, mini) = minimizer(&seq2bits(b"GTC", encoding), 3)?;
+        assert_eq!(offset, 0);
+        assert_eq!(mini, seq2bits(b"GTC", encoding));
+
+        assert!(matches!(
+            minimizer(&seq2bits(b"GTC", encoding), 4),
+            Err(error::Error::Kff(error::Kff::MinimizerSizeTooLarge(4, 3)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rev_comp_() {
+        for encoding in [0b00011011, 0b00011110, 0b11100100] {
+            assert_eq!(
+                rev_comp(&seq2bits(b"ACTGG", encoding), encoding),
+                seq2bits(b"CCAGT", encoding)
+            );
+            assert_eq!(
+                canonical(&seq2bits(b"TTTAC", encoding), encoding),
+                std::cmp::min(seq2bits(b"TTTAC", encoding), seq2bits(b"GTAAA", encoding))
+            );
+        }
+
+        assert_eq!(rev_comp(&seq2bits(b"", 0b00011011), 0b00011011).len(), 0);
+    }
 }
@@ -0,0 +1,368 @@
+//! Sidecar index of first and last kmer of each kmer section
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+use crate::section;
+use crate::KffRead;
+use crate::KffWrite;
+use crate::Seq2Bit;
+
+/// Magic number at begin of a kmer index
+const MAGIC: &[u8; 4] = b"KFFI";
+
+/// Bounds of a kmer section
+///
+/// `first` and `last` are the smallest and largest kmers of section by their 2 bits
+/// representation, so order depends on encoding.
+#[derive(
+    getset::Getters, std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+#[getset(get = "pub")]
+pub struct SectionBounds {
+    /// Position of section from begin of file, section flag include
+    position: u64,
+
+    /// Section type, `r` or `m`
+    section_type: u8,
+
+    /// Size of kmer
+    k: u64,
+
+    /// Number of kmer in section
+    nb_kmer: u64,
+
+    /// Minimizer of section, None for a Raw section
+    minimizer: Option<Seq2Bit>,
+
+    /// Smallest kmer of section
+    first: Seq2Bit,
+
+    /// Largest kmer of section
+    last: Seq2Bit,
+}
+
+impl SectionBounds {
+    /// Compute bounds of a section begin at `position`, None if section is empty
+    pub fn new(position: u64, section: section::KmerSection) -> Option<Self> {
+        let (section_type, minimizer) = match section.minimizer() {
+            Some(minimizer) => (b'm', Some(minimizer.clone())),
+            None => (b'r', None),
+        };
+
+        let k = *section.blocks().first()?.k();
+
+        let mut nb_kmer = 0;
+        let mut bounds: Option<(Seq2Bit, Seq2Bit)> = None;
+        for kmer in section.into_blocks().into_iter().flatten() {
+            nb_kmer += 1;
+
+            bounds = Some(match bounds {
+                None => (kmer.seq2bit().clone(), kmer.seq2bit().clone()),
+                Some((first, last)) => (
+                    std::cmp::min(first, kmer.seq2bit().clone()),
+                    std::cmp::max(last, kmer.seq2bit().clone()),
+                ),
+            });
+        }
+        let (first, last) = bounds?;
+
+        Some(Self {
+            position,
+            section_type,
+            k,
+            nb_kmer,
+            minimizer,
+            first,
+            last,
+        })
+    }
+
+    /// Return true if `kmer` has size `k` and is between first and last kmer of section
+    pub fn may_contain(&self, kmer: &Seq2Bit) -> bool {
+        kmer.len() as u64 == self.k * 2 && &self.first <= kmer && kmer <= &self.last
+    }
+
+    /// Return true if range `start..=end` of kmers of size `k` overlap section
+    pub fn overlap(&self, start: &Seq2Bit, end: &Seq2Bit) -> bool {
+        start.len() as u64 == self.k * 2 && start <= &self.last && &self.first <= end
+    }
+
+    fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + KffRead,
+    {
+        let section_type = inner.read_u8()?;
+        let position = inner.read_u64()?;
+        let k = inner.read_u64()?;
+        let nb_kmer = inner.read_u64()?;
+
+        let minimizer = match section_type {
+            b'm' => {
+                let m = inner.read_u64()?;
+                Some(inner.read_2bits(m as usize)?.into_boxed_bitslice())
+            }
+            b'r' => None,
+            e => return Err(error::Kff::NotASectionPrefix(e).into()),
+        };
+
+        let first = inner.read_2bits(k as usize)?.into_boxed_bitslice();
+        let last = inner.read_2bits(k as usize)?.into_boxed_bitslice();
+
+        Ok(Self {
+            position,
+            section_type,
+            k,
+            nb_kmer,
+            minimizer,
+            first,
+            last,
+        })
+    }
+
+    fn write<W>(&self, outer: &mut W) -> error::Result<()>
+    where
+        W: std::io::Write + KffWrite,
+    {
+        outer.write_u8(&self.section_type)?;
+        outer.write_u64(&self.position)?;
+        outer.write_u64(&self.k)?;
+        outer.write_u64(&self.nb_kmer)?;
+
+        if let Some(minimizer) = self.minimizer.as_ref() {
+            outer.write_u64(&(minimizer.len() as u64 / 2))?;
+            outer.write_bytes(minimizer.as_raw_slice())?;
+        }
+
+        outer.write_bytes(self.first.as_raw_slice())?;
+        outer.write_bytes(self.last.as_raw_slice())
+    }
+}
+
+/// Sidecar index that store [SectionBounds] of each kmer section of a KFF file
+///
+/// Build it with [Kff::kmer_index](crate::Kff::kmer_index), sections are in file order. Use
+/// [Kff::block_section_at](crate::Kff::block_section_at) to read a selected section. Index is
+/// store in a separate file, it begins with `KFFI`, number of sections and bounds of each
+/// section, integers are big endian.
+#[derive(
+    getset::Getters, std::clone::Clone, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+#[getset(get = "pub")]
+pub struct KmerIndex {
+    /// Bounds of each kmer section
+    sections: Vec<SectionBounds>,
+}
+
+impl KmerIndex {
+    /// Build a KmerIndex from bounds of sections
+    pub fn from_sections(sections: Vec<SectionBounds>) -> Self {
+        Self { sections }
+    }
+
+    /// Sections that may contain `kmer`
+    pub fn contains<'a>(&'a self, kmer: &'a Seq2Bit) -> impl Iterator<Item = &'a SectionBounds> {
+        self.sections.iter().filter(move |s| s.may_contain(kmer))
+    }
+
+    /// Sections that may contain kmers in range `start..=end`
+    pub fn range<'a>(
+        &'a self,
+        start: &'a Seq2Bit,
+        end: &'a Seq2Bit,
+    ) -> impl Iterator<Item = &'a SectionBounds> {
+        self.sections.iter().filter(move |s| s.overlap(start, end))
+    }
+
+    /// Minimizer sections with this minimizer
+    pub fn with_minimizer<'a>(
+        &'a self,
+        minimizer: &'a Seq2Bit,
+    ) -> impl Iterator<Item = &'a SectionBounds> {
+        self.sections
+            .iter()
+            .filter(move |s| s.minimizer.as_ref() == Some(minimizer))
+    }
+
+    /// Read a KmerIndex
+    pub fn read<R>(inner: &mut R) -> error::Result<Self>
+    where
+        R: std::io::Read + KffRead,
+    {
+        if &inner.read_n_bytes::<4>()? != MAGIC {
+            return Err(error::Kff::NotAKmerIndex.into());
+        }
+
+        let nb_section = inner.read_u64()?;
+        let sections = (0..nb_section)
+            .map(|_| SectionBounds::read(inner))
+            .collect::<error::Result<Vec<_>>>()?;
+
+        Ok(Self { sections })
+    }
+
+    /// Write a KmerIndex
+    pub fn write<W>(&self, outer: &mut W) -> error::Result<()>
+    where
+        W: std::io::Write + KffWrite,
+    {
+        outer.write_bytes(MAGIC)?;
+        outer.write_u64(&(self.sections.len() as u64))?;
+
+        for section in self.sections.iter() {
+            section.write(outer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a KmerIndex from file match with path
+    pub fn load<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut inner = std::io::BufReader::new(std::fs::File::open(path)?);
+
+        Self::read(&mut inner)
+    }
+
+    /// Write a KmerIndex in file match with path
+    pub fn save<P>(&self, path: P) -> error::Result<()>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let mut outer = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+        self.write(&mut outer)?;
+        std::io::Write::flush(&mut outer)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::section::values::AbcValues as _;
+    use crate::Kff;
+    use crate::Kmer;
+
+    const ENCODING: u8 = 0b00011011;
+
+    fn file() -> Vec<u8> {
+        let header = section::Header::new(1, 0, ENCODING, true, false, b"".to_vec()).unwrap();
+        let mut writer = Kff::write(Vec::new(), header).unwrap();
+
+        let mut values = section::Values::new();
+        values.insert("k".to_string(), 3);
+        values.insert("m".to_string(), 2);
+        values.insert("max".to_string(), 1);
+        values.insert("data_size".to_string(), 0);
+        writer.write_values(values.clone()).unwrap();
+
+        let blocks = |kmers: &[&[u8]]| -> Vec<section::Block> {
+            kmers
+                .iter()
+                .map(|seq| section::Block::new(3, 0, Kmer::from_ascii(seq, vec![], ENCODING), 0))
+                .collect()
+        };
+
+        writer
+            .write_raw(
+                section::Raw::new(&values).unwrap(),
+                &blocks(&[b"GTA", b"ACG", b"CCC"]),
+            )
+            .unwrap();
+        writer
+            .write_minimizer(
+                section::Minimizer::new(&values).unwrap(),
+                crate::kmer::seq2bits(b"TT", ENCODING),
+                &blocks(&[b"TTA", b"TTG"]),
+            )
+            .unwrap();
+        writer.finalize_with_index().unwrap();
+
+        writer.into_inner()
+    }
+
+    fn seq(seq: &[u8]) -> Seq2Bit {
+        crate::kmer::seq2bits(seq, ENCODING)
+    }
+
+    #[test]
+    fn build() -> error::Result<()> {
+        let mut reader = Kff::read_with_index(std::io::Cursor::new(file()))?;
+        let index = reader.kmer_index()?;
+
+        assert_eq!(index.sections().len(), 2);
+
+        let raw = &index.sections()[0];
+        assert_eq!(raw.section_type(), &b'r');
+        assert_eq!(raw.nb_kmer(), &3);
+        assert_eq!(raw.first(), &seq(b"ACG"));
+        assert_eq!(raw.last(), &seq(b"GTA"));
+        assert_eq!(raw.minimizer(), &None);
+
+        let minimizer = &index.sections()[1];
+        assert_eq!(minimizer.section_type(), &b'm');
+        assert_eq!(minimizer.minimizer(), &Some(seq(b"TT")));
+        assert_eq!(minimizer.first(), &seq(b"TTA"));
+        assert_eq!(minimizer.last(), &seq(b"TTG"));
+
+        let kmers: Vec<Vec<u8>> = reader
+            .block_section_at(*minimizer.position())?
+            .into_blocks()
+            .into_iter()
+            .flatten()
+            .map(|k| k.seq(ENCODING))
+            .collect();
+        assert_eq!(kmers, vec![b"TTA".to_vec(), b"TTG".to_vec()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn query() -> error::Result<()> {
+        let index = Kff::read_with_index(std::io::Cursor::new(file()))?.kmer_index()?;
+
+        let kmer = seq(b"CAT");
+        let positions: Vec<u64> = index.contains(&kmer).map(|s| *s.position()).collect();
+        assert_eq!(positions, vec![*index.sections()[0].position()]);
+
+        let kmer = seq(b"GGG");
+        assert_eq!(index.contains(&kmer).count(), 0);
+
+        let kmer = seq(b"CA");
+        assert_eq!(index.contains(&kmer).count(), 0);
+
+        let (start, end) = (seq(b"CCA"), seq(b"TTA"));
+        assert_eq!(index.range(&start, &end).count(), 2);
+
+        let minimizer = seq(b"TT");
+        assert_eq!(index.with_minimizer(&minimizer).count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_write() -> error::Result<()> {
+        let index = Kff::read_with_index(std::io::Cursor::new(file()))?.kmer_index()?;
+
+        let mut buffer = Vec::new();
+        index.write(&mut buffer)?;
+        assert_eq!(&buffer[..4], b"KFFI");
+
+        assert_eq!(KmerIndex::read(&mut std::io::Cursor::new(buffer))?, index);
+
+        assert!(matches!(
+            KmerIndex::read(&mut std::io::Cursor::new(b"KFF\x01".to_vec())),
+            Err(error::Error::Kff(error::Kff::NotAKmerIndex))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Limits on sizes read in a KFF file
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+
+/// Maximal sizes a reader accept before allocate memory
+///
+/// A corrupt or malicious file can claim any size, decoders check each size read against these
+/// limits and return [error::Kff::LimitExceeded] instead of allocate it. Default limits accept
+/// any reasonable file, [ReadLimits::unlimited] disable all checks.
+#[derive(
+    getset::Getters,
+    getset::Setters,
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct ReadLimits {
+    /// Maximal number of variables in a Values section
+    max_variables: u64,
+
+    /// Maximal size in bytes of header free block
+    max_free_block: u64,
+
+    /// Maximal size in bytes of a block, sequence and data
+    max_block_length: u64,
+
+    /// Maximal size in bytes of blocks of a kmer section
+    max_section_size: u64,
+}
+
+impl ReadLimits {
+    /// Limits that accept any size
+    pub fn unlimited() -> Self {
+        Self {
+            max_variables: u64::MAX,
+            max_free_block: u64::MAX,
+            max_block_length: u64::MAX,
+            max_section_size: u64::MAX,
+        }
+    }
+}
+
+impl std::default::Default for ReadLimits {
+    fn default() -> Self {
+        Self {
+            max_variables: 1 << 12,
+            max_free_block: 1 << 24,
+            max_block_length: 1 << 30,
+            max_section_size: 1 << 32,
+        }
+    }
+}
+
+/// Return an error if `value` is larger than `limit`
+pub(crate) fn check(name: &str, value: u64, limit: u64) -> error::Result<()> {
+    if value > limit {
+        Err(error::Kff::LimitExceeded(name.to_string(), value, limit).into())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_() {
+        assert!(check("max_variables", 12, 12).is_ok());
+        assert!(matches!(
+            check("max_variables", 13, 12),
+            Err(error::Error::Kff(error::Kff::LimitExceeded(name, 13, 12))) if name == "max_variables"
+        ));
+
+        let mut limits = ReadLimits::default();
+        limits.set_max_variables(2);
+        assert_eq!(limits.max_variables(), &2);
+        assert_eq!(ReadLimits::unlimited().max_section_size(), &u64::MAX);
+    }
+}
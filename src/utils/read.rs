@@ -15,6 +15,9 @@ pub trait KffRead {
     /// Function read N bytes (N define at run time) in a readable
     fn read_n_bytes_dyn(&mut self, n: usize) -> error::Result<Vec<u8>>;
 
+    /// Function skip N bytes (N define at run time) in a readable
+    fn skip_n_bytes(&mut self, n: usize) -> error::Result<()>;
+
     /// Function read a Kff 'ascii'
     fn read_ascii(&mut self) -> error::Result<Vec<u8>>;
 
@@ -69,13 +72,37 @@ where
     }
 
     fn read_n_bytes_dyn(&mut self, n: usize) -> error::Result<Vec<u8>> {
-        let mut values = vec![0; n];
-
-        self.read_exact(&mut values)?;
+        // Buffer grow with bytes really read, a corrupt size can't allocate more than input
+        let mut values = Vec::with_capacity(std::cmp::min(n, 1 << 16));
+
+        std::io::Read::read_to_end(
+            &mut std::io::Read::take(self.by_ref(), n as u64),
+            &mut values,
+        )?;
+        if values.len() != n {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
 
         Ok(values)
     }
 
+    fn skip_n_bytes(&mut self, n: usize) -> error::Result<()> {
+        let mut remain = n;
+
+        while remain != 0 {
+            let available = self.fill_buf()?.len();
+            if available == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+
+            let consume = std::cmp::min(available, remain);
+            self.consume(consume);
+            remain -= consume;
+        }
+
+        Ok(())
+    }
+
     fn read_ascii(&mut self) -> error::Result<Vec<u8>> {
         let mut values = Vec::with_capacity(50);
 
@@ -93,7 +120,7 @@ where
         k: usize,
     ) -> error::Result<bitvec::vec::BitVec<u8, bitvec::order::Msb0>> {
         let mut values = bitvec::vec::BitVec::from_slice(
-            &self.read_n_bytes_dyn(crate::bytes2store_k(k as u64) as usize)?,
+            &self.read_n_bytes_dyn(k / 4 + usize::from(k % 4 != 0))?,
         );
 
         values.resize(k * 2, false);
@@ -102,6 +129,108 @@ where
     }
 }
 
+/// A reader that keep a copy of all bytes read in inner reader
+pub(crate) struct Recorder<R> {
+    inner: R,
+    record: Vec<u8>,
+    pending: Pending,
+}
+
+impl<R> Recorder<R>
+where
+    R: std::io::BufRead,
+{
+    /// Wrap a reader
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            record: Vec::new(),
+            pending: Pending::default(),
+        }
+    }
+
+    /// Consume Recorder to get bytes read
+    pub(crate) fn into_record(self) -> Vec<u8> {
+        self.record
+    }
+
+    /// Forget bytes read until now and return their number
+    pub(crate) fn clear_record(&mut self) -> u64 {
+        let len = self.record.len() as u64;
+        self.record.clear();
+
+        len
+    }
+}
+
+impl<R> std::io::Read for Recorder<R>
+where
+    R: std::io::BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.pending.consume(len);
+        self.record.extend_from_slice(&buf[..len]);
+
+        Ok(len)
+    }
+}
+
+impl<R> std::io::BufRead for Recorder<R>
+where
+    R: std::io::BufRead,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        let buf = self.inner.fill_buf()?;
+        self.pending.fill(buf);
+
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.record.extend_from_slice(self.pending.consume(amt));
+        self.inner.consume(amt)
+    }
+}
+
+/// Copy of bytes return by `fill_buf` of a reader and not yet consumed
+///
+/// A wrapper that observe consumed bytes can't call `fill_buf` of inner reader again in `consume`,
+/// an error at this point would be lost with bytes. It keeps this copy instead.
+#[derive(std::default::Default)]
+pub(crate) struct Pending {
+    bytes: Vec<u8>,
+    start: usize,
+}
+
+impl Pending {
+    /// Update copy with buffer return by `fill_buf` of inner reader
+    pub(crate) fn fill(&mut self, buf: &[u8]) {
+        let known = self.bytes.len() - self.start;
+
+        if buf.len() < known {
+            // Inner reader drop unconsumed bytes, restart from its buffer
+            self.bytes.clear();
+            self.start = 0;
+            self.bytes.extend_from_slice(buf);
+        } else {
+            if self.start > self.bytes.len() / 2 {
+                self.bytes.drain(..self.start);
+                self.start = 0;
+            }
+            self.bytes.extend_from_slice(&buf[known..]);
+        }
+    }
+
+    /// Remove `amt` first bytes of copy and return them
+    pub(crate) fn consume(&mut self, amt: usize) -> &[u8] {
+        let begin = self.start;
+        self.start = (self.start + amt).min(self.bytes.len());
+
+        &self.bytes[begin..self.start]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,6 +274,24 @@ mod tests {
 
         assert!(values.is_err());
 
+        // Size larger than memory doesn't allocate
+        let values = reader.read_n_bytes_dyn(usize::MAX);
+
+        assert!(values.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_n_bytes() -> error::Result<()> {
+        let mut reader = std::io::Cursor::new(LOREM);
+
+        reader.skip_n_bytes(6)?;
+
+        assert_eq!(&reader.read_n_bytes::<5>()?, b"ipsum");
+
+        assert!(reader.skip_n_bytes(400).is_err());
+
         Ok(())
     }
 
@@ -235,4 +382,80 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn recorder() -> error::Result<()> {
+        let mut reader = std::io::Cursor::new(LOREM);
+
+        let mut recorder = Recorder::new(&mut reader);
+        recorder.read_u8()?;
+        recorder.skip_n_bytes(5)?;
+        recorder.read_ascii()?;
+
+        assert_eq!(recorder.into_record(), b"Lorem ipsum dolor\0");
+        assert_eq!(reader.read_n_bytes::<3>()?, *b"sit");
+
+        // Mix of read and consume with a small buffer
+        let mut reader = std::io::BufReader::with_capacity(4, LOREM);
+        let mut recorder = Recorder::new(&mut reader);
+        recorder.read_n_bytes::<3>()?;
+        recorder.skip_n_bytes(3)?;
+        recorder.read_u32()?;
+        recorder.read_ascii()?;
+        recorder.read_n_bytes_dyn(5)?;
+
+        assert_eq!(recorder.into_record(), b"Lorem ipsum dolor\0sit a");
+
+        // Only bytes read after clear are kept
+        let mut recorder = Recorder::new(std::io::Cursor::new(LOREM));
+        recorder.read_n_bytes::<6>()?;
+        assert_eq!(recorder.clear_record(), 6);
+        recorder.read_ascii()?;
+        assert_eq!(recorder.into_record(), b"ipsum dolor\0");
+
+        Ok(())
+    }
+
+    /// A reader that fail on each fill_buf call after the first one
+    struct Flaky<'a> {
+        inner: &'a [u8],
+        filled: bool,
+    }
+
+    impl<'a> std::io::Read for Flaky<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl<'a> std::io::BufRead for Flaky<'a> {
+        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+            if self.filled {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            self.filled = true;
+
+            Ok(self.inner)
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.inner.consume(amt)
+        }
+    }
+
+    #[test]
+    fn recorder_consume_without_fill_buf() {
+        let mut reader = Flaky {
+            inner: LOREM,
+            filled: false,
+        };
+
+        let mut recorder = Recorder::new(&mut reader);
+        let len = std::io::BufRead::fill_buf(&mut recorder).unwrap().len();
+        std::io::BufRead::consume(&mut recorder, 5);
+        std::io::BufRead::consume(&mut recorder, 6);
+
+        assert_eq!(len, LOREM.len());
+        assert_eq!(recorder.into_record(), b"Lorem ipsum");
+    }
 }
@@ -60,6 +60,54 @@ where
     }
 }
 
+/// A writer that count number of bytes write in inner writer, and update a checksum if set
+pub(crate) struct Counter<'a, W> {
+    inner: &'a mut W,
+    count: u64,
+    checksum: Option<&'a mut crate::utils::Crc32c>,
+}
+
+impl<'a, W> Counter<'a, W>
+where
+    W: std::io::Write,
+{
+    /// Wrap a writer, bytes write are add to `checksum`
+    pub(crate) fn with_checksum(
+        inner: &'a mut W,
+        checksum: Option<&'a mut crate::utils::Crc32c>,
+    ) -> Self {
+        Self {
+            inner,
+            count: 0,
+            checksum,
+        }
+    }
+
+    /// Number of bytes write
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<'a, W> std::io::Write for Counter<'a, W>
+where
+    W: std::io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.count += len as u64;
+        if let Some(checksum) = self.checksum.as_mut() {
+            checksum.update(&buf[..len]);
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,4 +197,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn counter() -> error::Result<()> {
+        let mut writer = Vec::new();
+
+        let mut counter = Counter::with_checksum(&mut writer, None);
+        counter.write_ascii(b"Lorem")?;
+        counter.write_u64(&42)?;
+
+        assert_eq!(counter.count(), 14);
+        assert_eq!(writer.len(), 14);
+
+        let mut checksum = crate::utils::Crc32c::new();
+        let mut writer = Vec::new();
+        let mut counter = Counter::with_checksum(&mut writer, Some(&mut checksum));
+        counter.write_bytes(b"123456789")?;
+        assert_eq!(counter.count(), 9);
+        assert_eq!(checksum.value(), 0xE306_9283);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,169 @@
+//! Check kff binary produce the good output
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str], stdin: &[u8]) -> kff::error::Result<Vec<u8>> {
+    let mut child = Command::new("cargo")
+        .args(["run", "--features", "cli", "--bin", "kff", "--"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Couldn't create cargo bin child");
+
+    let write = child.stdin.take().unwrap().write_all(stdin);
+    let output = child.wait_with_output().expect("Error durring kff run");
+    write?;
+
+    if !output.status.success() {
+        panic!(
+            "\nstdout: {:?}\nstderr: {}",
+            output.stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+#[test]
+fn info() -> kff::error::Result<()> {
+    let output = run(&["info", "-i", "tests/data/test.kff"], b"")?;
+
+    assert_eq!(
+        String::from_utf8(output)?,
+        "version: 1.0
+encoding: 0b00011110
+uniq: false
+canonical: false
+free block: 
+values: data_size=0 k=31 max=255 ordered=0
+section 0: raw 118 bytes
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate() -> kff::error::Result<()> {
+    run(&["validate", "-i", "tests/data/test.kff"], b"")?;
+
+    Ok(())
+}
+
+#[test]
+fn repair() -> kff::error::Result<()> {
+    let kff = std::fs::read("tests/data/test.kff")?;
+
+    // Without final signature
+    let repaired = run(&["repair"], &kff[..kff.len() - 3])?;
+    assert_eq!(
+        run(&["view"], &repaired)?,
+        run(&["view", "-i", "tests/data/test.kff"], b"")?
+    );
+    run(&["validate"], &repaired)?;
+
+    Ok(())
+}
+
+#[test]
+fn text_round_trip() -> kff::error::Result<()> {
+    let text = b"ACTGA\t3\nCTGAC\t2\nAAAAA\t255\n";
+
+    let kff = run(&["from-text", "-d", "1"], text)?;
+    assert_eq!(run(&["view"], &kff)?, text);
+
+    let sorted = run(&["sort"], &kff)?;
+    assert_eq!(
+        run(&["view"], &sorted)?,
+        b"AAAAA\t255\nACTGA\t3\nCTGAC\t2\n"
+    );
+
+    let stats: serde_json::Value = serde_json::from_slice(&run(&["stats"], &kff)?).unwrap();
+    assert_eq!(stats["nb_kmer"], 3);
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    file.write_all(&kff)?;
+    let path = file.path().to_str().unwrap();
+
+    let merged = run(&["merge", "-i", path, path], b"")?;
+    assert_eq!(run(&["view"], &merged)?, [&text[..], &text[..]].concat());
+
+    Ok(())
+}
+
+#[test]
+fn free_block() -> kff::error::Result<()> {
+    let kff = run(&["from-text", "-d", "1"], b"ACTGA\t3\n")?;
+    let info = String::from_utf8(run(&["info"], &kff)?)?;
+
+    assert!(info.contains("producer: kff from-text"));
+    let date = info
+        .lines()
+        .find_map(|line| line.strip_prefix("date: "))
+        .unwrap();
+    assert!(date.len() == 10 && date.as_bytes()[4] == b'-' && date.as_bytes()[7] == b'-');
+    assert!(date[..4].parse::<u32>().unwrap() >= 2024);
+
+    Ok(())
+}
+
+#[test]
+fn from_reads() -> kff::error::Result<()> {
+    let reads = b">1\nACTGACTNACTGA\n>2\nGGGG\n";
+
+    let kff = run(&["from-reads", "-k", "5", "-m", "3"], reads)?;
+    assert_eq!(run(&["view"], &kff)?, b"ACTGA\t2\nTGACT\t1\nCTGAC\t1\n");
+
+    Ok(())
+}
+
+#[test]
+fn to_fasta() -> kff::error::Result<()> {
+    let kff = run(&["from-text", "-d", "1"], b"ACT\t1\nCTG\t2\nTGA\t3\n")?;
+
+    assert_eq!(
+        run(&["to-fasta"], &kff)?,
+        b">0 nb_kmer=1 data=1\nACT\n>1 nb_kmer=1 data=2\nCTG\n>2 nb_kmer=1 data=3\nTGA\n"
+    );
+    assert_eq!(
+        run(&["to-fasta", "--unitigs"], &kff)?,
+        b">0 nb_kmer=3 data=1,2,3\nACTGA\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn jellyfish() -> kff::error::Result<()> {
+    let kff = run(
+        &["from-text", "--format", "fasta", "-d", "2"],
+        b">300\nACTGA\n>2\nCTGAC\n",
+    )?;
+
+    assert_eq!(
+        run(&["view", "-f", "column"], &kff)?,
+        b"ACTGA 300\nCTGAC 2\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn query() -> kff::error::Result<()> {
+    let kff = run(&["from-text", "-d", "1"], b"ACTGA\t3\nCTGAC\t2\n")?;
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    file.write_all(&kff)?;
+    let path = file.path().to_str().unwrap();
+
+    assert_eq!(
+        run(&["query", "-i", path], b">read\nACTGACT\n>short\nAC\n")?,
+        b"0\t2\t3\t110\n1\t0\t0\t\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Check example produce the good output
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+#[test]
+fn kff_stats() -> kff::error::Result<()> {
+    let args = vec![
+        "run",
+        "--features",
+        "serde",
+        "--example",
+        "kff_stats",
+        "--",
+        "-i",
+        "tests/data/test.kff",
+    ];
+
+    let mut child = Command::new("cargo")
+        .args(&args)
+        .stderr(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Couldn't create cargo example child");
+
+    if !child.wait().expect("Error durring kff_stats run").success() {
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+
+        child.stdout.unwrap().read_to_string(&mut stdout)?;
+        child.stderr.unwrap().read_to_string(&mut stderr)?;
+
+        panic!("\nstdout: {}\nstderr: {}", stdout, stderr);
+    }
+
+    let mut value_str = String::new();
+    child.stdout.unwrap().read_to_string(&mut value_str)?;
+
+    let value: serde_json::Value = serde_json::from_str(&value_str).unwrap();
+
+    assert_eq!(value["nb_kmer"], 85);
+    assert_eq!(value["nb_block"], 10);
+    assert_eq!(value["data_bytes"], 0);
+    assert_eq!(value["values"]["k"], serde_json::json!([31]));
+    assert_eq!(value["sections"].as_array().unwrap().len(), 1);
+    assert_eq!(value["sections"][0]["section_type"], "r");
+
+    Ok(())
+}
@@ -50,7 +50,7 @@ fn kff2kmers() -> kff::error::Result<()> {
         1, 1, // Uniq, Canonical
         0, 0, 0, 24, // Free space size length
         112, 114, 111, 100, 117, 99, 101, 114, 58, 32, 107, 109, 101, 114, 95, 99, 111, 117, 110,
-        116, 50, 107, 102, 102, 0, b'v', 0, 0, 0, 0, 0, 0, 0, 4, // Four value
+        116, 50, 107, 102, 102, b'v', 0, 0, 0, 0, 0, 0, 0, 4, // Four value
         b'k', 0, 0, 0, 0, 0, 0, 0, 0, 31, //
         b'm', b'a', b'x', 0, 0, 0, 0, 0, 0, 0, 0, 200, //
         b'd', b'a', b't', b'a', b'_', b's', b'i', b'z', b'e', 0, 0, 0, 0, 0, 0, 0, 0, 1, //
@@ -0,0 +1,233 @@
+//! Write random KFF files and check reading produce the same content
+
+use kff::section::values::AbcValues as _;
+use proptest::prelude::*;
+
+/// Parameters of a random block, sequence and data are truncated to block size
+#[derive(Debug, Clone)]
+struct BlockSpec {
+    nb_kmer: u64,
+    seq: Vec<u8>,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+/// Parameters of a random kmer section, preceded by a Values section
+#[derive(Debug, Clone)]
+struct SectionSpec {
+    minimizer: bool,
+    k: u64,
+    m: u64,
+    max: u64,
+    data_size: u64,
+    extra: Vec<(String, u64)>,
+    blocks: Vec<BlockSpec>,
+}
+
+/// A kmer section ready to be write and expected result of read
+struct Section {
+    values: kff::section::Values,
+    minimizer: Option<kff::Seq2Bit>,
+    blocks: Vec<kff::section::Block>,
+}
+
+fn encoding() -> impl Strategy<Value = u8> {
+    Just([0u8, 1, 2, 3])
+        .prop_shuffle()
+        .prop_map(|n| n[0] << 6 | n[1] << 4 | n[2] << 2 | n[3])
+}
+
+fn header() -> impl Strategy<Value = kff::section::Header> {
+    (
+        0u8..=1,
+        encoding(),
+        any::<bool>(),
+        any::<bool>(),
+        proptest::collection::vec(any::<u8>(), 0..32),
+    )
+        .prop_map(|(major, encoding, uniq, canonical, free_block)| {
+            kff::section::Header::new(major, 0, encoding, uniq, canonical, free_block).unwrap()
+        })
+}
+
+fn max() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(1),
+        2u64..=255,
+        Just(256),
+        Just(u16::MAX as u64 + 1),
+        Just(u32::MAX as u64 + 1),
+        Just(u64::MAX),
+    ]
+}
+
+fn block() -> impl Strategy<Value = BlockSpec> {
+    (
+        any::<u64>(),
+        proptest::collection::vec(proptest::sample::select(&b"ACGT"[..]), 40),
+        proptest::collection::vec(any::<u8>(), 40),
+        any::<usize>(),
+    )
+        .prop_map(|(nb_kmer, seq, data, offset)| BlockSpec {
+            nb_kmer,
+            seq,
+            data,
+            offset,
+        })
+}
+
+fn section() -> impl Strategy<Value = SectionSpec> {
+    (
+        any::<bool>(),
+        1u64..32,
+        any::<u64>(),
+        max(),
+        0u64..4,
+        proptest::collection::vec(("[a-z_]{1,10}", any::<u64>()), 0..3),
+        proptest::collection::vec(block(), 0..5),
+    )
+        .prop_map(
+            |(minimizer, k, m, max, data_size, extra, blocks)| SectionSpec {
+                minimizer,
+                k,
+                m: 1 + m % k,
+                max,
+                data_size,
+                extra,
+                blocks,
+            },
+        )
+}
+
+/// Build values, minimizer and blocks of a section
+fn build(spec: &SectionSpec, encoding: u8) -> Section {
+    let mut values = kff::section::Values::new();
+    for (name, value) in spec.extra.iter() {
+        values.insert(name.clone(), *value);
+    }
+    values.insert("k".to_string(), spec.k);
+    values.insert("max".to_string(), spec.max);
+    values.insert("data_size".to_string(), spec.data_size);
+    if spec.minimizer {
+        values.insert("m".to_string(), spec.m);
+    }
+
+    let k = spec.k as usize;
+    let m = spec.m as usize;
+    let minimizer = spec
+        .blocks
+        .first()
+        .map(|block| block.seq[..m].to_vec())
+        .unwrap_or_else(|| vec![b'A'; m]);
+
+    let blocks = spec
+        .blocks
+        .iter()
+        .map(|block| {
+            let nb_kmer = 1 + (block.nb_kmer % std::cmp::min(spec.max, 8)) as usize;
+            let mut seq = block.seq[..k + nb_kmer - 1].to_vec();
+            let data = block.data[..nb_kmer * spec.data_size as usize].to_vec();
+
+            // Minimizer is in all kmers of block
+            let offset = if spec.minimizer {
+                let offset = nb_kmer - 1 + block.offset % (k - m + 1);
+                seq[offset..offset + m].copy_from_slice(&minimizer);
+                offset
+            } else {
+                0
+            };
+
+            kff::section::Block::new(
+                spec.k,
+                spec.data_size as usize,
+                kff::Kmer::from_ascii(&seq, data, encoding),
+                offset,
+            )
+        })
+        .collect();
+
+    Section {
+        values,
+        minimizer: spec
+            .minimizer
+            .then(|| kff::kmer::seq2bits(&minimizer, encoding)),
+        blocks,
+    }
+}
+
+fn write(
+    header: &kff::section::Header,
+    sections: &[Section],
+    index: bool,
+) -> kff::error::Result<Vec<u8>> {
+    let mut writer = kff::Kff::write(Vec::new(), header.clone())?;
+
+    for section in sections {
+        writer.write_values(section.values.clone())?;
+
+        match &section.minimizer {
+            None => writer.write_raw(kff::section::Raw::new(&section.values)?, &section.blocks)?,
+            Some(minimizer) => writer.write_minimizer(
+                kff::section::Minimizer::new(&section.values)?,
+                minimizer.clone(),
+                &section.blocks,
+            )?,
+        }
+    }
+
+    if index {
+        writer.finalize_with_index()?;
+    } else {
+        writer.finalize()?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn round_trip(
+        header in header(),
+        specs in proptest::collection::vec(section(), 0..5),
+        index in any::<bool>(),
+    ) {
+        let encoding = *header.encoding();
+        let sections: Vec<Section> = specs.iter().map(|spec| build(spec, encoding)).collect();
+
+        let buffer = write(&header, &sections, index)?;
+
+        let mut reader = kff::Kff::read(std::io::Cursor::new(buffer.clone()))?;
+        prop_assert_eq!(reader.header().major_version(), header.major_version());
+        prop_assert_eq!(reader.header().encoding(), header.encoding());
+        prop_assert_eq!(reader.header().uniq_kmer(), header.uniq_kmer());
+        prop_assert_eq!(reader.header().canonical_kmer(), header.canonical_kmer());
+        prop_assert_eq!(reader.header().free_block(), header.free_block());
+
+        for section in sections.iter() {
+            let read = reader.next_block_section().unwrap()?;
+
+            prop_assert_eq!(reader.values(), &section.values);
+            prop_assert_eq!(read.minimizer(), section.minimizer.as_ref());
+            prop_assert_eq!(read.blocks(), &section.blocks[..]);
+        }
+        prop_assert!(reader.next_block_section().is_none());
+
+        // Kmer iterator produce all kmers in order
+        let expected: Vec<kff::Kmer> = sections
+            .into_iter()
+            .flat_map(|section| section.blocks.into_iter().flatten())
+            .collect();
+        let kmers = kff::Kff::read(std::io::Cursor::new(buffer.clone()))?
+            .kmers()
+            .collect::<kff::error::Result<Vec<kff::Kmer>>>()?;
+        prop_assert_eq!(kmers, expected);
+
+        if index {
+            let reader = kff::Kff::read_with_index(std::io::Cursor::new(buffer))?;
+            let pairs = reader.index().as_ref().unwrap().pair();
+            prop_assert_eq!(pairs.len(), specs.len() * 2);
+        }
+    }
+}